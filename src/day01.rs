@@ -1,6 +1,6 @@
 use std::fs;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{AdventError, ExclusivePart, RunContext};
 
 const INPUT_FILE: &str = "./resources/day01_input.txt";
 
@@ -10,7 +10,7 @@ enum Extrema {
     Last,
 }
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
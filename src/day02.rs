@@ -1,10 +1,13 @@
 use std::{collections::HashMap, fs};
 
-use crate::{utils::integers_from_string, AdventError, ExclusivePart};
+use crate::{
+    utils::{self, ParseError},
+    AdventError, ExclusivePart, RunContext,
+};
 
 const INPUT_FILE: &str = "./resources/day02_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -19,19 +22,9 @@ fn part_one() -> Result<String, AdventError> {
     let mut possible_ids = Vec::new();
 
     // parse input
-    for line in input.lines() {
-        let mut split = line.split(':');
-        let game_id = integers_from_string(split.next().unwrap(), " ")[0];
-        let reveals: Vec<&str> = split.next().unwrap().split(';').collect();
-
-        let mut reveals = reveals.iter().map(|s| {
-            let mut colors = HashMap::new();
-            for c in s.split(',') {
-                let (num, color) = c.trim().split_once(' ').unwrap();
-                colors.insert(color, num.parse::<i32>().unwrap());
-            }
-            colors
-        });
+    for game in parse_games(&input)? {
+        let game_id = game.id;
+        let mut reveals = game.reveals.into_iter();
 
         if !reveals.any(|m| {
             *m.get("red").unwrap_or(&0) > 12
@@ -55,19 +48,8 @@ fn part_two() -> Result<String, AdventError> {
     let mut powers = Vec::new();
 
     // parse input
-    for line in input.lines() {
-        let mut split = line.split(':');
-        let _game_id = integers_from_string::<i32>(split.next().unwrap(), " ")[0];
-        let reveals: Vec<&str> = split.next().unwrap().split(';').collect();
-
-        let reveals = reveals.iter().map(|s| {
-            let mut colors = HashMap::new();
-            for c in s.split(',') {
-                let (num, color) = c.trim().split_once(' ').unwrap();
-                colors.insert(color, num.parse::<i32>().unwrap());
-            }
-            colors
-        });
+    for game in parse_games(&input)? {
+        let reveals = game.reveals;
 
         let mut mins = HashMap::new();
         for m in reveals {
@@ -97,3 +79,37 @@ fn part_two() -> Result<String, AdventError> {
 
     Ok(result)
 }
+
+struct Game<'a> {
+    id: i32,
+    /// How many cubes of each color were shown in each reveal
+    reveals: Vec<HashMap<&'a str, i32>>,
+}
+
+/// Parse lines like `Game 1: 3 blue, 4 red; 1 red, 2 green`
+fn parse_games(input: &str) -> Result<Vec<Game<'_>>, ParseError> {
+    utils::line_by_line(input, |cursor| {
+        cursor.expect_literal("Game")?;
+        cursor.spaces()?;
+        let id = cursor.integer()?;
+        cursor.expect_literal(":")?;
+
+        let reveals = cursor.separated_list(
+            |cursor| cursor.expect_literal(";"),
+            |cursor| {
+                let colors = cursor.separated_list(
+                    |cursor| cursor.expect_literal(","),
+                    |cursor| {
+                        cursor.skip_spaces();
+                        let count = cursor.integer()?;
+                        cursor.spaces()?;
+                        Ok((cursor.identifier()?, count))
+                    },
+                )?;
+                Ok(colors.into_iter().collect())
+            },
+        )?;
+
+        Ok(Game { id, reveals })
+    })
+}
@@ -1,10 +1,10 @@
 use std::fs;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{AdventError, ExclusivePart, RunContext};
 
 const INPUT_FILE: &str = "./resources/day03_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -51,7 +51,6 @@ fn part_one() -> Result<String, AdventError> {
             };
             if c.is_numeric() {
                 // found a number! where does it end...?
-                // println!("found a number at {y},{c_index}: {c}");
                 let start_index = c_index;
                 let mut sub_c_index = c_index + 1;
                 let mut end_index = start_index;
@@ -94,16 +93,11 @@ fn part_one() -> Result<String, AdventError> {
         // unpack
         let NumberLocation { ref num_string, .. } = location;
 
-        // println!("checking: row {row_index} from {x1} to {x2}: {num_string}");
-
         // check border for symbols
         let borders_symbol = is_num_bordering_symbol(&rows, &location);
 
         if borders_symbol {
-            // println!("  adding {num_string}");
             valid_part_nums.push(num_string.parse::<u32>().unwrap())
-        } else {
-            // println!("  skipping {num_string}, no symbol found");
         }
     }
 
@@ -154,7 +148,6 @@ fn part_two() -> Result<String, AdventError> {
 
         let mut num_set: Vec<NumberLocation> = Vec::new();
         for (x, y) in get_neighbors(column_index as i32, row_index as i32) {
-            // println!("  checking {x},{y} for * at {column_index},{row_index}");
             let neighbor_number_location = match get_number_location_from_coordinates(&rows, x, y) {
                 Some(location) => location,
                 None => {
@@ -172,10 +165,6 @@ fn part_two() -> Result<String, AdventError> {
                 continue;
             }
 
-            // println!(
-            //     "  adding {} for * at {column_index},{row_index}",
-            //     neighbor_number_location.num_string
-            // );
             num_set.push(neighbor_number_location);
         }
         num_set
@@ -184,7 +173,6 @@ fn part_two() -> Result<String, AdventError> {
     let gears = potential_gears.filter(|num_set| num_set.len() == 2);
 
     let ratios = gears.map(|gear| {
-        // println!("{:?}", gear);
         let a = gear[0].num_string.parse::<u32>().unwrap();
         let b = gear[1].num_string.parse::<u32>().unwrap();
         a * b
@@ -1,10 +1,13 @@
 use std::{collections::HashMap, fs};
 
-use crate::{AdventError, ExclusivePart};
+use crate::{
+    utils::{self, ParseError},
+    AdventError, ExclusivePart, RunContext,
+};
 
 const INPUT_FILE: &str = "./resources/day04_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -17,31 +20,17 @@ fn part_one() -> Result<String, AdventError> {
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
     // parse input
-    let card_matches = input.lines().map(|line| {
-        let (_, nums) = line.split_once(':').unwrap();
-        let (winners, owned) = nums.split_once('|').unwrap();
-
-        let winners: Vec<&str> = winners
-            .trim()
-            .split(' ')
-            .filter(|s| !s.is_empty())
-            .collect();
-        let owned = owned.trim().split(' ').filter(|s| !s.is_empty());
-
-        let matches = owned
-            .filter(move |owned| winners.iter().any(|winner| **winner == **owned))
-            .map(|s| s.to_owned());
-
-        matches
-    });
+    let card_matches = parse_cards(&input)?
+        .into_iter()
+        .map(|card| card.matching_numbers());
 
     let card_points = card_matches.map(|matching_nums| {
-        let len = matching_nums.count() as u32;
-        let points = if len > 0 { (2 as u32).pow(len) / 2 } else { 0 };
-
-        // println!("len: {}, points: {}", len, points);
-
-        points
+        let len = matching_nums as u32;
+        if len > 0 {
+            (2 as u32).pow(len) / 2
+        } else {
+            0
+        }
     });
 
     let result = card_points.sum::<u32>();
@@ -55,25 +44,9 @@ fn part_two() -> Result<String, AdventError> {
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
     // parse input
-    let card_matches: Vec<Vec<String>> = input
-        .lines()
-        .map(|line| {
-            let (_, nums) = line.split_once(':').unwrap();
-            let (winners, owned) = nums.split_once('|').unwrap();
-
-            let winners: Vec<&str> = winners
-                .trim()
-                .split(' ')
-                .filter(|s| !s.is_empty())
-                .collect();
-            let owned = owned.trim().split(' ').filter(|s| !s.is_empty());
-
-            let matches = owned
-                .filter(move |owned| winners.iter().any(|winner| **winner == **owned))
-                .map(|s| s.to_owned());
-
-            matches.collect()
-        })
+    let card_matches: Vec<usize> = parse_cards(&input)?
+        .iter()
+        .map(Card::matching_numbers)
         .collect();
 
     let mut card_counts = HashMap::new();
@@ -88,31 +61,18 @@ fn part_two() -> Result<String, AdventError> {
         let card_num = index as u32 + 1;
 
         let current_card_instance_count = *card_counts.get(&card_num).unwrap();
-        // println!(
-        //     "processing card #{} with {} instances",
-        //     card_num, current_card_instance_count
-        // );
 
-        let match_count = card_match_count.len() as u32;
+        let match_count = *card_match_count as u32;
         if match_count == 0 {
             continue;
         }
         for copy_num in (card_num + 1)..=((card_num + match_count).min(max_card_num)) {
             // add a copy of the cards we won
-            let (_old_count, _new_count) =
-                if let Some(current_won_card_count) = card_counts.get_mut(&copy_num) {
-                    let old_instance_count = *current_won_card_count;
-                    let new_instance_count = *current_won_card_count + current_card_instance_count;
-                    *current_won_card_count = new_instance_count;
-                    (old_instance_count, new_instance_count)
-                } else {
-                    card_counts.insert(copy_num, current_card_instance_count);
-                    (0, match_count)
-                };
-            // println!(
-            //     "incrementing #{} by {} (COPY) [{} -> {}]",
-            //     copy_num, current_card_instance_count, old_count, new_count,
-            // );
+            if let Some(current_won_card_count) = card_counts.get_mut(&copy_num) {
+                *current_won_card_count += current_card_instance_count;
+            } else {
+                card_counts.insert(copy_num, current_card_instance_count);
+            }
         }
     }
 
@@ -122,3 +82,36 @@ fn part_two() -> Result<String, AdventError> {
 
     Ok(total_copies.to_string())
 }
+
+struct Card {
+    winners: Vec<u32>,
+    owned: Vec<u32>,
+}
+
+impl Card {
+    /// How many of the owned numbers are also winning numbers
+    fn matching_numbers(&self) -> usize {
+        self.owned
+            .iter()
+            .filter(|owned| self.winners.contains(owned))
+            .count()
+    }
+}
+
+/// Parse lines like `Card 1: 41 48 83 | 83 86  6 31`
+fn parse_cards(input: &str) -> Result<Vec<Card>, ParseError> {
+    utils::line_by_line(input, |cursor| {
+        cursor.expect_literal("Card")?;
+        cursor.spaces()?;
+        cursor.integer::<u32>()?;
+        cursor.expect_literal(":")?;
+        cursor.spaces()?;
+        let winners = cursor.separated_list(|cursor| cursor.spaces(), |cursor| cursor.integer())?;
+        cursor.spaces()?;
+        cursor.expect_literal("|")?;
+        cursor.spaces()?;
+        let owned = cursor.separated_list(|cursor| cursor.spaces(), |cursor| cursor.integer())?;
+
+        Ok(Card { winners, owned })
+    })
+}
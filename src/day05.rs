@@ -1,16 +1,24 @@
-use std::{
-    fmt::Display,
-    fs,
-    ops::{self},
-};
-
-use auto_ops::{impl_op, impl_op_ex};
+use std::{fs, ops};
 
-use crate::{utils, AdventError, ExclusivePart};
+use crate::{
+    utils::{HeaderBlocks, Interval, RangeMap, SortedDisjointIntervalList},
+    AdventError, ExclusivePart, RunContext,
+};
 
 const INPUT_FILE: &str = "./resources/day05_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+/// The headers of every map in the almanac, in the order seeds pass through them
+const MAP_NAMES: [&str; 7] = [
+    "seed-to-soil map",
+    "soil-to-fertilizer map",
+    "fertilizer-to-water map",
+    "water-to-light map",
+    "light-to-temperature map",
+    "temperature-to-humidity map",
+    "humidity-to-location map",
+];
+
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -22,13 +30,11 @@ fn part_one() -> Result<String, AdventError> {
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let almanac = Almanac::build_from_string(&input);
-
-    let seed_to_location = almanac.seed_to_location_intervals();
-    let seed_id_ranges = almanac.seed_id_ranges(false);
-    let final_ranges = &seed_id_ranges & &seed_to_location;
+    let almanac = Almanac::build_from_string(&input)?;
 
-    let minimum_location = final_ranges.minimum_output().unwrap();
+    let minimum_location = almanac
+        .lowest_location(false)
+        .ok_or("almanac has no seeds")?;
 
     Ok(minimum_location.to_string())
 }
@@ -38,13 +44,11 @@ fn part_two() -> Result<String, AdventError> {
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let almanac = Almanac::build_from_string(&input);
+    let almanac = Almanac::build_from_string(&input)?;
 
-    let seed_to_location = almanac.seed_to_location_intervals();
-    let seed_id_ranges = almanac.seed_id_ranges(true);
-    let final_ranges = &seed_id_ranges & &seed_to_location;
-
-    let minimum_location = final_ranges.minimum_output().unwrap();
+    let minimum_location = almanac
+        .lowest_location(true)
+        .ok_or("almanac has no seeds")?;
 
     Ok(minimum_location.to_string())
 }
@@ -52,105 +56,71 @@ fn part_two() -> Result<String, AdventError> {
 struct Almanac {
     seed_ids: Vec<i64>,
 
-    seed_to_soil: SortedDisjointIntervalList,
-    soil_to_fertilizer: SortedDisjointIntervalList,
-    fertilizer_to_water: SortedDisjointIntervalList,
-    water_to_light: SortedDisjointIntervalList,
-    light_to_temperature: SortedDisjointIntervalList,
-    temperature_to_humidity: SortedDisjointIntervalList,
-    humidity_to_location: SortedDisjointIntervalList,
-    // seed_to_fertilizer: SortedDisjointIntervalList,
-    // seed_to_water: SortedDisjointIntervalList,
-    // seed_to_light: SortedDisjointIntervalList,
-    // seed_to_temperature: SortedDisjointIntervalList,
-    // seed_to_humidity: SortedDisjointIntervalList,
-    // seed_to_location: SortedDisjointIntervalList,
+    seed_to_soil: RangeMap,
+    soil_to_fertilizer: RangeMap,
+    fertilizer_to_water: RangeMap,
+    water_to_light: RangeMap,
+    light_to_temperature: RangeMap,
+    temperature_to_humidity: RangeMap,
+    humidity_to_location: RangeMap,
 }
 
 impl Almanac {
-    fn build_from_string(input: &str) -> Self {
-        let mut lines = input.lines();
+    fn build_from_string(input: &str) -> Result<Self, String> {
+        let blocks = HeaderBlocks::parse(input)?;
 
         // parse seed ids
-
-        let seed_ids = utils::integers_from_string::<i64>(
-            lines.next().unwrap().split_once(':').unwrap().1.trim(),
-            " ",
-        );
-
-        // skip blank line and seed map line
-        lines.next();
-        lines.next();
-
-        // parse mapping data
+        let seed_ids = blocks
+            .expect("seeds")?
+            .lines(|cursor| {
+                cursor.separated_list(|cursor| cursor.spaces(), |cursor| cursor.integer::<i64>())
+            })?
+            .concat();
+
+        // parse mapping data, one map per header, in the order seeds pass through them
         let mut property_maps = Vec::new();
-        let mut range_maps = Vec::new();
-        for line in lines {
-            // skip blank lines
-            if line.is_empty() {
-                continue;
-            }
-
-            // switch to next map if line contains "map"
-            if line.contains("map") {
-                property_maps.push(range_maps);
-                range_maps = Vec::new();
-                continue;
-            }
-
-            // parse range mapping
-            let mapping_nums = utils::integers_from_string::<u64>(line, " ");
-            range_maps.push(UnparsedRange {
-                destination_start: mapping_nums[0],
-                source_start: mapping_nums[1],
-                length: mapping_nums[2],
-            });
+        for name in MAP_NAMES {
+            let range_maps = blocks.expect(name)?.lines(|cursor| {
+                let destination = cursor.integer()?;
+                cursor.spaces()?;
+                let source = cursor.integer()?;
+                cursor.spaces()?;
+                let length = cursor.integer()?;
+                Ok((destination, source, length))
+            })?;
+            property_maps.push(RangeMap::from_destination_source_lengths(range_maps)?);
         }
 
-        // add last map
-        property_maps.push(range_maps);
-
-        Almanac {
+        Ok(Almanac {
             seed_ids,
 
-            seed_to_soil: property_maps.remove(0).into(),
-            soil_to_fertilizer: property_maps.remove(0).into(),
-            fertilizer_to_water: property_maps.remove(0).into(),
-            water_to_light: property_maps.remove(0).into(),
-            light_to_temperature: property_maps.remove(0).into(),
-            temperature_to_humidity: property_maps.remove(0).into(),
-            humidity_to_location: property_maps.remove(0).into(),
-        }
+            seed_to_soil: property_maps.remove(0),
+            soil_to_fertilizer: property_maps.remove(0),
+            fertilizer_to_water: property_maps.remove(0),
+            water_to_light: property_maps.remove(0),
+            light_to_temperature: property_maps.remove(0),
+            temperature_to_humidity: property_maps.remove(0),
+            humidity_to_location: property_maps.remove(0),
+        })
     }
 
-    fn seed_id_ranges(&self, interpret_as_ranges: bool) -> SortedDisjointIntervalList {
-        let seed_ranges: Vec<ParsedRange> = if interpret_as_ranges {
+    fn seed_id_ranges(&self, interpret_as_ranges: bool) -> SortedDisjointIntervalList<()> {
+        if interpret_as_ranges {
             self.get_seed_ranges()
                 .into_iter()
-                .map(|range| ParsedRange {
-                    source_range: range,
-                    offset: 0,
-                    shift: 0,
-                })
+                .map(Interval::new)
                 .collect()
         } else {
             self.seed_ids
-                .clone()
-                .into_iter()
-                .map(|seed_id| ParsedRange {
-                    source_range: seed_id..(seed_id + 1),
-                    offset: 0,
-                    shift: 0,
-                })
+                .iter()
+                .map(|&seed_id| Interval::new(seed_id..(seed_id + 1)))
                 .collect()
-        };
-        SortedDisjointIntervalList::new(seed_ranges)
+        }
     }
 
     fn get_seed_ranges(&self) -> Vec<ops::Range<i64>> {
         let mut seed_iter = self.seed_ids.iter();
         let mut iter_ranges = Vec::new();
-        // println!("getting seed ranges...");
         loop {
             // maybe get new range
             let start = match seed_iter.next() {
@@ -166,541 +136,181 @@ impl Almanac {
         iter_ranges
     }
 
-    fn seed_to_location_intervals(&self) -> SortedDisjointIntervalList {
-        let seed_to_fertilizer = &self.seed_to_soil + &self.soil_to_fertilizer;
-        let seed_to_water = &seed_to_fertilizer + &self.fertilizer_to_water;
-        let seed_to_light = &seed_to_water + &self.water_to_light;
-        let seed_to_temperature = &seed_to_light + &self.light_to_temperature;
-        let seed_to_humidity = &seed_to_temperature + &self.temperature_to_humidity;
-        let seed_to_location = &seed_to_humidity + &self.humidity_to_location;
-
-        seed_to_location
+    /// The whole chain of maps, flattened into one
+    fn seed_to_location(&self) -> RangeMap {
+        self.seed_to_soil
+            .then(&self.soil_to_fertilizer)
+            .then(&self.fertilizer_to_water)
+            .then(&self.water_to_light)
+            .then(&self.light_to_temperature)
+            .then(&self.temperature_to_humidity)
+            .then(&self.humidity_to_location)
     }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct UnparsedRange {
-    destination_start: u64,
-    source_start: u64,
-    length: u64,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct ParsedRange {
-    pub source_range: ops::Range<i64>,
-    pub offset: i64,
-    pub shift: i64,
-}
 
-impl ParsedRange {
-    fn shift_by(&self, shift: i64) -> ParsedRange {
-        ParsedRange {
-            source_range: self.source_range.start + shift..self.source_range.end + shift,
-            offset: self.offset,
-            shift: self.shift + shift,
-        }
-    }
+    fn lowest_location(&self, interpret_as_ranges: bool) -> Option<i64> {
+        let locations = self
+            .seed_to_location()
+            .apply(&self.seed_id_ranges(interpret_as_ranges));
 
-    fn unshift(&self) -> ParsedRange {
-        self.shift_by(-self.shift)
-    }
-
-    fn intersect(&self, other: &ParsedRange) -> Option<ParsedRange> {
-        let a = &self.source_range;
-        let b = &other.source_range;
-
-        let left = a.start.max(b.start);
-        let right = a.end.min(b.end);
-
-        if left < right {
-            Some(ParsedRange {
-                source_range: left..right,
-                offset: self.offset + other.offset,
-                shift: self.shift + other.shift,
-            })
-        } else {
-            None
-        }
-    }
-
-    fn subtract(&self, other: &ParsedRange) -> Option<Vec<ParsedRange>> {
-        match self.intersect(other) {
-            // if there's some intersection, figure out which part to remove
-            Some(intersection) => {
-                let a = &self.source_range;
-                let int = &intersection.source_range;
-
-                if a.start == int.start && a.end == int.end {
-                    // we are encompassed by the other range, so we are removed when subtracted
-                    None
-                } else if a.start == int.start {
-                    // the left side will be clipped off, since that's where the intersection aligns
-                    Some(vec![ParsedRange {
-                        source_range: int.end..a.end,
-                        offset: self.offset,
-                        shift: self.shift,
-                    }])
-                } else if a.end == int.end {
-                    // the right side will be clipped off, since that's where the intersection aligns
-                    Some(vec![ParsedRange {
-                        source_range: a.start..int.start,
-                        offset: self.offset,
-                        shift: self.shift,
-                    }])
-                } else {
-                    // we completely encompass the intersection, so we must segment ourself
-                    Some(vec![
-                        ParsedRange {
-                            source_range: a.start..int.start,
-                            offset: self.offset,
-                            shift: self.shift,
-                        },
-                        ParsedRange {
-                            source_range: int.end..a.end,
-                            offset: self.offset,
-                            shift: self.shift,
-                        },
-                    ])
-                }
-            }
-            // no intersection means nothing to subtract! :)
-            None => Some(vec![self.clone()]),
-        }
+        locations
+            .intervals()
+            .first()
+            .map(|interval| interval.start())
     }
 }
 
-impl_op_ex!(&|a: &ParsedRange, b: &ParsedRange| -> Option<ParsedRange> { a.intersect(b) });
-
-impl_op_ex!(-|a: &ParsedRange, b: &ParsedRange| -> Option<Vec<ParsedRange>> { a.subtract(b) });
-
-impl Display for ParsedRange {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({:?}, {:+})", self.source_range, self.offset)
-    }
-}
-
-impl From<UnparsedRange> for ParsedRange {
-    fn from(unparsed_range: UnparsedRange) -> Self {
-        ParsedRange {
-            source_range: unparsed_range.source_start as i64
-                ..(unparsed_range.source_start + unparsed_range.length) as i64,
-            offset: unparsed_range.destination_start as i64 - unparsed_range.source_start as i64,
-            shift: 0,
-        }
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct SortedDisjointIntervalList {
-    pub intervals: Vec<ParsedRange>,
-}
+    type OffsetMap = SortedDisjointIntervalList<i64>;
 
-impl SortedDisjointIntervalList {
-    fn new(mut intervals: Vec<ParsedRange>) -> Self {
-        intervals.sort_by(|a, b| a.source_range.start.cmp(&b.source_range.start));
-        Self { intervals }
+    fn offset_of(interval: &Interval<i64>) -> i64 {
+        interval.data().iter().sum()
     }
 
-    fn minimum_output(&self) -> Option<i64> {
-        self.intervals
+    fn offset_map(ranges: &[(ops::Range<i64>, i64)]) -> OffsetMap {
+        ranges
             .iter()
-            .map(|range| range.source_range.start + range.offset)
-            .min()
+            .map(|(range, offset)| Interval::new_with_data(range.clone(), *offset))
+            .collect()
     }
 
-    fn shift_by_offsets(&self) -> Self {
-        Self::new(
-            self.intervals
-                .iter()
-                .map(|range| range.shift_by(range.offset))
-                .collect(),
-        )
-    }
-
-    fn unshift(&self) -> Self {
-        Self::new(self.intervals.iter().map(|range| range.unshift()).collect())
+    fn offsets(map: &OffsetMap) -> Vec<(ops::Range<i64>, i64)> {
+        map.iter()
+            .map(|interval| (interval.range().clone(), offset_of(interval)))
+            .collect()
     }
 
-    fn intersect(&self, other: &Self) -> SortedDisjointIntervalList {
-        let mut ai: usize = 0;
-        let mut bi: usize = 0;
-
-        let mut intersections = Vec::new();
-        while ai < self.intervals.len() && bi < other.intervals.len() {
-            let a = &self.intervals[ai];
-            let b = &other.intervals[bi];
-
-            if let Some(intersection) = a & b {
-                intersections.push(intersection);
-            }
-
-            if a.source_range.end < b.source_range.end {
-                ai += 1;
-            } else {
-                bi += 1;
-            }
-        }
-
-        SortedDisjointIntervalList::new(intersections)
-    }
-
-    fn subtract(&self, other: &Self) -> SortedDisjointIntervalList {
-        let mut subtractions = Vec::new();
-        for a in &self.intervals {
-            let mut subtraction: Vec<ParsedRange> = vec![a.clone()];
-            for b in &other.intervals {
-                if let Some(a_comp) = subtraction.last() {
-                    match a_comp - b {
-                        Some(s) => subtraction = s,
-                        None => subtraction = vec![],
-                    }
-                }
-            }
-            subtractions.extend(subtraction);
-        }
-        SortedDisjointIntervalList::new(subtractions)
-    }
-
-    fn merge(&self, other: &Self) -> SortedDisjointIntervalList {
-        self.internal_merge(other, false)
-    }
-
-    fn internal_merge(&self, other: &Self, debug: bool) -> SortedDisjointIntervalList {
-        let self_shifted = self.shift_by_offsets();
-        let projection_intersection = &self_shifted & &other;
-        let sub_self = &self_shifted - &projection_intersection;
-        let sub_other = other - &projection_intersection;
-
-        let res = SortedDisjointIntervalList::new(
-            sub_self
-                .intervals
-                .iter()
-                .chain(sub_other.intervals.iter())
-                .chain(projection_intersection.intervals.iter())
-                .cloned()
-                .collect(),
-        )
-        .unshift();
-
-        if debug {
-            println!("A\t\t{}", self);
-            println!("B\t\t{}", other);
-            println!("A shift\t\t{}", self.shift_by_offsets());
-            println!("A proj B\t{}", projection_intersection);
-            println!("A - (A proj B)\t{}", sub_self);
-            println!("B - (A proj B)\t{}", sub_other);
-            println!("A + B\t\t{}", res);
-        }
-
-        res
-    }
-}
-
-impl From<Vec<ParsedRange>> for SortedDisjointIntervalList {
-    fn from(intervals: Vec<ParsedRange>) -> Self {
-        SortedDisjointIntervalList::new(intervals)
-    }
-}
-
-impl From<Vec<UnparsedRange>> for SortedDisjointIntervalList {
-    fn from(intervals: Vec<UnparsedRange>) -> Self {
-        SortedDisjointIntervalList::new(
-            intervals
-                .iter()
-                .map(|unparsed_range| ParsedRange::from(*unparsed_range))
-                .collect(),
-        )
-    }
-}
-
-impl_op!(&|a: &SortedDisjointIntervalList,
-           b: &SortedDisjointIntervalList|
- -> SortedDisjointIntervalList { a.intersect(b) });
-
-impl_op!(-|a: &SortedDisjointIntervalList,
-           b: &SortedDisjointIntervalList|
- -> SortedDisjointIntervalList { a.subtract(b) });
-
-impl_op!(+|a: &SortedDisjointIntervalList,
-              b: &SortedDisjointIntervalList|
- -> SortedDisjointIntervalList { a.merge(b) });
-
-impl Display for SortedDisjointIntervalList {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let range_strs = self.intervals.iter().map(|range| range.to_string());
-        let str = range_strs.collect::<Vec<String>>().join(", ");
-        write!(f, "[{}]", str)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
     #[test]
     fn basic_intersect_single() {
-        let a = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 0..10,
-            offset: 3,
-            shift: 0,
-        }]);
-        let b = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 5..15,
-            offset: -2,
-            shift: 0,
-        }]);
+        let a = offset_map(&[(0..10, 3)]);
+        let b = offset_map(&[(5..15, -2)]);
 
         let a_and_b = &a & &b;
 
-        assert_eq!(
-            a_and_b.intervals,
-            vec![ParsedRange {
-                source_range: 5..10,
-                offset: 1,
-                shift: 0,
-            }]
-        );
+        assert_eq!(offsets(&a_and_b), vec![(5..10, 1)]);
     }
 
     #[test]
     fn basic_subtraction_single() {
-        let a = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 0..10,
-            offset: 3,
-            shift: 0,
-        }]);
-        let b = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 5..15,
-            offset: -2,
-            shift: 0,
-        }]);
+        let a = offset_map(&[(0..10, 3)]);
+        let b = offset_map(&[(5..15, -2)]);
 
         let a_minus_b = &a - &b;
 
-        assert_eq!(
-            a_minus_b.intervals,
-            vec![ParsedRange {
-                source_range: 0..5,
-                offset: 3,
-                shift: 0,
-            }]
-        );
+        assert_eq!(offsets(&a_minus_b), vec![(0..5, 3)]);
     }
 
     #[test]
     fn basic_intersect_double() {
-        let a = SortedDisjointIntervalList::new(vec![
-            ParsedRange {
-                source_range: 0..10,
-                offset: 3,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 20..30,
-                offset: 9,
-                shift: 0,
-            },
-        ]);
-        let b = SortedDisjointIntervalList::new(vec![
-            ParsedRange {
-                source_range: 5..15,
-                offset: -2,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 15..25,
-                offset: 15,
-                shift: 0,
-            },
-        ]);
+        let a = offset_map(&[(0..10, 3), (20..30, 9)]);
+        let b = offset_map(&[(5..15, -2), (15..25, 15)]);
 
         let a_and_b = &a & &b;
 
-        assert_eq!(
-            a_and_b.intervals,
-            vec![
-                ParsedRange {
-                    source_range: 5..10,
-                    offset: 1,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 20..25,
-                    offset: 24,
-                    shift: 0,
-                },
-            ]
-        );
+        assert_eq!(offsets(&a_and_b), vec![(5..10, 1), (20..25, 24)]);
     }
 
     #[test]
     fn basic_subtraction_double() {
-        let a = SortedDisjointIntervalList::new(vec![
-            ParsedRange {
-                source_range: 0..10,
-                offset: 3,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 20..30,
-                offset: 9,
-                shift: 0,
-            },
-        ]);
-        let b = SortedDisjointIntervalList::new(vec![
-            ParsedRange {
-                source_range: 5..15,
-                offset: -2,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 15..25,
-                offset: 15,
-                shift: 0,
-            },
-        ]);
+        let a = offset_map(&[(0..10, 3), (20..30, 9)]);
+        let b = offset_map(&[(5..15, -2), (15..25, 15)]);
 
         let a_minus_b = &a - &b;
 
-        assert_eq!(
-            a_minus_b.intervals,
-            vec![
-                ParsedRange {
-                    source_range: 0..5,
-                    offset: 3,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 25..30,
-                    offset: 9,
-                    shift: 0,
-                },
-            ]
-        );
+        assert_eq!(offsets(&a_minus_b), vec![(0..5, 3), (25..30, 9)]);
     }
 
     #[test]
     fn basic_intersect_single_overlapping() {
-        let a = SortedDisjointIntervalList::new(vec![
-            ParsedRange {
-                source_range: 0..10,
-                offset: 3,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 20..30,
-                offset: 9,
-                shift: 0,
-            },
-        ]);
-        let b = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 5..25,
-            offset: -4,
-            shift: 0,
-        }]);
+        let a = offset_map(&[(0..10, 3), (20..30, 9)]);
+        let b = offset_map(&[(5..25, -4)]);
 
         let a_and_b = &a & &b;
 
-        assert_eq!(
-            a_and_b.intervals,
-            vec![
-                ParsedRange {
-                    source_range: 5..10,
-                    offset: -1,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 20..25,
-                    offset: 5,
-                    shift: 0,
-                },
-            ]
-        );
+        assert_eq!(offsets(&a_and_b), vec![(5..10, -1), (20..25, 5)]);
     }
 
     #[test]
     fn basic_intersect_double_overlapping() {
-        let a = SortedDisjointIntervalList::new(vec![
-            ParsedRange {
-                source_range: 0..10,
-                offset: 3,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 20..30,
-                offset: 9,
-                shift: 0,
-            },
-            ParsedRange {
-                source_range: 40..50,
-                offset: 27,
-                shift: 0,
-            },
-        ]);
-        let b = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 5..45,
-            offset: -2,
-            shift: 0,
-        }]);
+        let a = offset_map(&[(0..10, 3), (20..30, 9), (40..50, 27)]);
+        let b = offset_map(&[(5..45, -2)]);
 
         let a_and_b = &a & &b;
 
         assert_eq!(
-            a_and_b.intervals,
-            vec![
-                ParsedRange {
-                    source_range: 5..10,
-                    offset: 1,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 20..30,
-                    offset: 7,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 40..45,
-                    offset: 25,
-                    shift: 0,
-                },
-            ]
+            offsets(&a_and_b),
+            vec![(5..10, 1), (20..30, 7), (40..45, 25)]
         );
     }
 
     #[test]
     fn basic_merge_single() {
-        let a = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 0..10,
-            offset: 3,
-            shift: 0,
-        }]);
-        let b = SortedDisjointIntervalList::new(vec![ParsedRange {
-            source_range: 5..15,
-            offset: -2,
-            shift: 0,
-        }]);
-
-        let ab = &a + &b;
+        let a = RangeMap::new([(0..10, 3)]).unwrap();
+        let b = RangeMap::new([(5..15, -2)]).unwrap();
+
+        let ab = a.then(&b);
 
+        // ids 10..15 aren't touched by `a`, so they go straight into `b`
         assert_eq!(
-            ab.intervals,
-            vec![
-                ParsedRange {
-                    source_range: 0..2,
-                    offset: 3,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 2..10,
-                    offset: 1,
-                    shift: 0,
-                },
-                ParsedRange {
-                    source_range: 13..15,
-                    offset: -2,
-                    shift: 0,
-                }
-            ]
-        )
+            ab.pieces()
+                .map(|(range, offset)| (range.clone(), offset))
+                .collect::<Vec<_>>(),
+            vec![(0..2, 3), (2..10, 1), (10..15, -2)]
+        );
+    }
+
+    #[test]
+    fn example_almanac() {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+        let almanac = Almanac::build_from_string(input).unwrap();
+
+        assert_eq!(almanac.lowest_location(false), Some(35));
+        assert_eq!(almanac.lowest_location(true), Some(46));
+
+        // every map in the example is a bijection, so we can go from locations back to seeds
+        let seed_to_location = almanac.seed_to_location();
+        let location_to_seed = seed_to_location.inverse().unwrap();
+        assert_eq!(seed_to_location.get(82), 46);
+        assert_eq!(location_to_seed.get(46), 82);
+        assert!(seed_to_location
+            .preimage(&SortedDisjointIntervalList::new(vec![Interval::<()>::new(
+                46..47
+            )]))
+            .contains(82));
     }
 }
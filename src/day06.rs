@@ -1,10 +1,13 @@
-use std::fs;
+use std::{fs, ops};
 
-use crate::{utils, AdventError, ExclusivePart};
+use crate::{
+    utils::{self, Cursor, ParseError},
+    AdventError, ExclusivePart, RunContext,
+};
 
 const INPUT_FILE: &str = "./resources/day06_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -16,39 +19,13 @@ fn part_one() -> Result<String, AdventError> {
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let mut lines = input.lines();
-
-    let times = utils::integers_from_string::<u64>(
-        lines.next().unwrap().split_once(':').unwrap().1.trim(),
-        " ",
-    );
-    let distances = utils::integers_from_string::<u64>(
-        lines.next().unwrap().split_once(':').unwrap().1.trim(),
-        " ",
-    );
-
-    if times.len() != distances.len() {
-        return Err(AdventError::Other(
-            "times and distances don't match".to_string(),
-        ));
-    }
+    let sheet = RaceSheet::parse(&input)?;
 
-    let mut races = Vec::new();
-    for i in 0..times.len() {
-        races.push(Race {
-            time_limit_ms: times[i],
-            distance_record_mm: distances[i],
-        })
+    let mut result = 1;
+    for race in &sheet.races()? {
+        result *= race.num_record_breaking_options()?;
     }
 
-    let num_way_to_win = races.iter().map(|race| {
-        let range = race.find_record_breaking_range();
-        // println!("{} to {}", range.0, range.1);
-        (range.1 - range.0) + 1
-    });
-
-    let result: u64 = num_way_to_win.product();
-
     Ok(result.to_string())
 }
 
@@ -57,62 +34,149 @@ fn part_two() -> Result<String, AdventError> {
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let mut lines = input.lines();
-
-    let time = lines
-        .next()
-        .unwrap()
-        .split_once(':')
-        .unwrap()
-        .1
-        .trim()
-        .replace(" ", "")
-        .parse::<u64>()
-        .map_err(|err| AdventError::Other(err.to_string()))?;
-
-    let distance = lines
-        .next()
-        .unwrap()
-        .split_once(':')
-        .unwrap()
-        .1
-        .trim()
-        .replace(" ", "")
-        .parse::<u64>()
-        .map_err(|err| AdventError::Other(err.to_string()))?;
-
-    let race = Race {
-        time_limit_ms: time,
-        distance_record_mm: distance,
-    };
-
-    let range = race.find_record_breaking_range();
-    // println!("{} to {}", range.0, range.1);
-    let winning_options_count = (range.1 - range.0) + 1;
+    let sheet = RaceSheet::parse(&input)?;
+
+    let race = sheet.single_race()?;
+
+    let winning_options_count = race.num_record_breaking_options()?;
 
     Ok(winning_options_count.to_string())
 }
 
+/// The sheet of paper listing the time limit and record distance for each race
+struct RaceSheet<'a> {
+    /// The numbers exactly as written, since part two ignores the spaces between them
+    times: Vec<&'a str>,
+    distances: Vec<&'a str>,
+}
+
+impl<'a> RaceSheet<'a> {
+    fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let mut headings = ["Time:", "Distance:"].into_iter();
+        let mut lines = utils::line_by_line(input, |cursor| {
+            let heading = headings
+                .next()
+                .ok_or_else(|| cursor.error("expected only Time and Distance lines"))?;
+            cursor.expect_literal(heading)?;
+            cursor.skip_spaces();
+            cursor.separated_list(Cursor::spaces, |cursor| {
+                // check it's a number that fits, but keep the digits as they are
+                let mut number = *cursor;
+                number.integer::<u64>()?;
+                Ok(cursor.take_while(|c| c.is_ascii_digit()))
+            })
+        })?
+        .into_iter();
+
+        match (lines.next(), lines.next()) {
+            (Some(times), Some(distances)) => Ok(Self { times, distances }),
+            _ => Err(Cursor::at_line("", input.lines().count() + 1)
+                .error("expected both Time and Distance lines")),
+        }
+    }
+
+    fn races(&self) -> Result<Vec<Race>, String> {
+        if self.times.len() != self.distances.len() {
+            return Err(format!(
+                "{} times but {} distances",
+                self.times.len(),
+                self.distances.len()
+            ));
+        }
+
+        self.times
+            .iter()
+            .zip(&self.distances)
+            .map(|(time, distance)| Race::parse(time, distance))
+            .collect()
+    }
+
+    /// The one long race written down with bad kerning
+    fn single_race(&self) -> Result<Race, String> {
+        Race::parse(&self.times.concat(), &self.distances.concat())
+    }
+}
+
 struct Race {
     time_limit_ms: u64,
     distance_record_mm: u64,
 }
 
 impl Race {
-    fn find_record_breaking_range(&self) -> (u64, u64) {
-        let options = 0..=self.time_limit_ms;
-
-        let mut min_winning_distance: u64 = 0;
-        let mut max_winning_distance: u64 = 0;
-        for option in options {
-            let total_distance = option * (self.time_limit_ms - option);
-            if total_distance > self.distance_record_mm && min_winning_distance == 0 {
-                min_winning_distance = option;
-            } else if total_distance > self.distance_record_mm {
-                max_winning_distance = option;
-            }
-        }
+    fn parse(time: &str, distance: &str) -> Result<Self, String> {
+        let parse = |digits: &str| {
+            digits
+                .parse::<u64>()
+                .map_err(|err| format!("invalid number {}: {}", digits, err))
+        };
+
+        Ok(Self {
+            time_limit_ms: parse(time)?,
+            distance_record_mm: parse(distance)?,
+        })
+    }
+
+    /// Every hold time that beats the record
+    fn find_record_breaking_range(&self) -> Result<Option<ops::RangeInclusive<u64>>, String> {
+        // holding for t ms leaves (limit - t) ms to travel at t mm/ms, so we need
+        // -t^2 + limit * t - record > 0
+        let range = utils::quadratic_positive_interval(
+            -1,
+            self.time_limit_ms as i128,
+            -(self.distance_record_mm as i128),
+        )?;
+
+        // the distance is never positive when holding for 0 or all of the ms,
+        // so the range always fits within the time limit
+        Ok(range.map(|range| *range.start() as u64..=*range.end() as u64))
+    }
+
+    fn num_record_breaking_options(&self) -> Result<u64, String> {
+        Ok(self
+            .find_record_breaking_range()?
+            .map_or(0, |range| range.end() - range.start() + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn test_example() {
+        let sheet = RaceSheet::parse(EXAMPLE).unwrap();
+
+        let options = sheet
+            .races()
+            .unwrap()
+            .iter()
+            .map(|race| race.num_record_breaking_options().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(options, [4, 8, 9]);
+
+        let race = sheet.single_race().unwrap();
+        assert_eq!(race.num_record_breaking_options(), Ok(71503));
+    }
 
-        (min_winning_distance, max_winning_distance)
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| RaceSheet::parse(input).err().map(|err| err.to_string());
+
+        assert_eq!(
+            error("Time:      7  1x5   30\nDistance:  9  40  200"),
+            Some("line 1 col 16: expected end of line found 'x'".to_string())
+        );
+        assert_eq!(
+            error("Time:      7  15   30\nDist:  9  40  200"),
+            Some("line 2 col 1: expected 'Distance:' found 'Dist:  9 '".to_string())
+        );
+        assert_eq!(
+            error("Time:      7  15   30"),
+            Some("line 2 col 1: expected both Time and Distance lines".to_string())
+        );
     }
 }
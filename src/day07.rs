@@ -1,44 +1,42 @@
 use std::{collections::HashMap, fs};
 
-use crate::{AdventError, ExclusivePart};
+use crate::{utils, AdventError, ExclusivePart, RunContext};
 
 const INPUT_FILE: &str = "./resources/day07_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
-    let total_winnings = get_total_winnings(false).map_err(|err| AdventError::Other(err))?;
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
+    let total_winnings = get_total_winnings(false, ctx).map_err(|err| AdventError::Other(err))?;
 
     Ok(total_winnings.to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
-    let total_winnings = get_total_winnings(true).map_err(|err| AdventError::Other(err))?;
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
+    let total_winnings = get_total_winnings(true, ctx).map_err(|err| AdventError::Other(err))?;
 
     Ok(total_winnings.to_string())
 }
 
-fn get_total_winnings(joker_mode: bool) -> Result<u64, String> {
+fn get_total_winnings(joker_mode: bool, ctx: &mut RunContext) -> Result<u64, String> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE).map_err(|err| err.to_string())?;
 
-    let hand_and_bids: Result<Vec<HandAndBid>, String> = input
-        .lines()
-        .map(|line| {
-            let (unparsed_hand, unparsed_bid) = line.split_once(" ").unwrap();
+    let mut hand_and_bids = utils::line_by_line(&input, |cursor| {
+        let hand_start = *cursor;
+        let unparsed_hand = cursor.take_while(|c| !c.is_whitespace());
+        let hand =
+            Hand::from_str(unparsed_hand, joker_mode).map_err(|err| hand_start.error(err))?;
+        cursor.spaces()?;
+        let bid = cursor.integer::<u64>()?;
 
-            let hand = Hand::from_str(unparsed_hand, joker_mode)?;
-            let bid = unparsed_bid.parse::<u64>().map_err(|err| err.to_string())?;
-
-            Ok(HandAndBid { hand, bid })
-        })
-        .collect();
-    let mut hand_and_bids = hand_and_bids?;
+        Ok(HandAndBid { hand, bid })
+    })?;
 
     // sort and rank hands
     hand_and_bids.sort();
@@ -52,15 +50,15 @@ fn get_total_winnings(joker_mode: bool) -> Result<u64, String> {
         })
         .collect();
 
-    // for hand in hand_bid_and_ranks.iter() {
-    //     println!(
-    //         "hand: {:?}\n  bid: {:?}, rank: {:?}, winnings: {:?}",
-    //         hand.hand,
-    //         hand.bid,
-    //         hand.rank,
-    //         hand.winnings()
-    //     );
-    // }
+    for hand in hand_bid_and_ranks.iter() {
+        ctx.trace(format_args!(
+            "hand: {:?}\n  bid: {:?}, rank: {:?}, winnings: {:?}",
+            hand.hand,
+            hand.bid,
+            hand.rank,
+            hand.winnings()
+        ));
+    }
 
     let total_winnings = hand_bid_and_ranks
         .iter()
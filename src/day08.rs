@@ -1,144 +1,199 @@
 use std::{char, collections::HashMap, fs};
 
-use num::Integer;
+use itertools::Itertools;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{
+    utils::{self, AdjacencyFormat, DiGraph, HitTimes},
+    AdventError, ExclusivePart, RunContext, Verbosity,
+};
 
 const INPUT_FILE: &str = "./resources/day08_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let map = Map::parse_from_string(&input);
+    let map = Map::parse_from_string(&input)?;
 
-    let steps = map.get_steps_to_end();
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(map.to_dot());
+    }
+
+    let steps = map.get_steps_to_end()?;
 
     Ok(steps.to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let map = Map::parse_from_string(&input);
+    let map = Map::parse_from_string(&input)?;
+
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(map.to_dot());
+    }
 
-    let steps = map.get_steps_to_all_ends();
+    let steps = map.get_steps_to_all_ends()?;
 
     Ok(steps.to_string())
 }
 
 struct Map {
     directions: Vec<Direction>,
-    nodes: HashMap<Node, NodeFork>,
+    graph: DiGraph<Node, Direction>,
 }
 
 impl Map {
-    fn parse_from_string(input: &str) -> Self {
-        let mut lines = input.lines();
+    fn parse_from_string(input: &str) -> Result<Self, String> {
+        let (directions_section, nodes_section) = match utils::sections(input)[..] {
+            [directions, nodes] => (directions, nodes),
+            ref sections => {
+                return Err(format!(
+                    "expected directions and then nodes, found {} sections",
+                    sections.len()
+                ))
+            }
+        };
 
         // parse directions
-        let directions: Vec<Direction> = lines
-            .next()
-            .unwrap()
-            .chars()
-            .map(|c| match c {
-                'L' => Direction::Left,
-                'R' => Direction::Right,
-                _ => panic!("Unknown direction: {}", c),
-            })
-            .collect();
+        let directions: Vec<Direction> = directions_section.parse(|directions| {
+            directions
+                .chars()
+                .map(|c| match c {
+                    'L' => Ok(Direction::Left),
+                    'R' => Ok(Direction::Right),
+                    _ => Err(format!("unknown direction: {}", c)),
+                })
+                .collect::<Result<_, _>>()
+        })?;
+
+        // parse node map, with an edge for each way out of a node
+        let format = AdjacencyFormat::assignment();
+        let lines = nodes_section.lines(|cursor| format.parse_line(cursor))?;
+        let mut graph = DiGraph::new();
+        for line in &lines {
+            let [left, right] = line.neighbors[..] else {
+                return Err(format!(
+                    "line {}: node {} should fork exactly two ways",
+                    line.line, line.name
+                ));
+            };
+            if let Some(name) = [line.name, left, right]
+                .into_iter()
+                .find(|name| name.chars().count() != 3)
+            {
+                return Err(format!(
+                    "line {}: node names should be 3 characters, found {}",
+                    line.line, name
+                ));
+            }
 
-        // consume blank line
-        lines.next();
+            let node_key = Node::new(line.name);
+            graph.add_edge(node_key, Node::new(left), Direction::Left);
+            graph.add_edge(node_key, Node::new(right), Direction::Right);
+        }
 
-        // parse node map
-        let mut nodes = HashMap::new();
-        for line in lines {
-            let (node_key, left_right) = line.split_once(" = ").unwrap();
+        Ok(Self { directions, graph })
+    }
 
-            let node_key = Node::new(node_key);
-            let node_fork = NodeFork::parse_from_string(left_right);
+    /// The node reached by going in `direction` from `node`
+    fn follow(&self, node: usize, direction: Direction) -> Result<usize, String> {
+        self.graph
+            .out_edges(node)
+            .find(|edge| edge.label == direction)
+            .map(|edge| edge.to)
+            .ok_or_else(|| {
+                let name = self.graph.node(node).id.iter().collect::<String>();
+                format!("node {} has no way {:?}", name, direction)
+            })
+    }
 
-            nodes.insert(node_key, node_fork);
-        }
+    fn index_of(&self, name: &str) -> Result<usize, String> {
+        self.graph
+            .index_of(&Node::new(name))
+            .ok_or_else(|| format!("there's no node {}", name))
+    }
 
-        Self { directions, nodes }
+    /// The network in Graphviz's dot format, with each edge labelled by its direction
+    fn to_dot(&self) -> String {
+        self.graph.to_dot(
+            |node| node.id.iter().collect(),
+            |direction| Some(format!("{:?}", direction)),
+        )
     }
 
-    fn get_steps_to_end(&self) -> u64 {
-        let end_node = Node::new("ZZZ");
+    fn get_steps_to_end(&self) -> Result<u64, String> {
+        let end_node = self.index_of("ZZZ")?;
 
         let mut steps = 0;
-        let mut current_node = Node::new("AAA");
+        let mut current_node = self.index_of("AAA")?;
         let mut dir_index = 0;
         loop {
             if current_node == end_node {
                 break;
             }
-            let dir = &self.directions[dir_index % self.directions.len()];
-            current_node = match dir {
-                Direction::Left => self.nodes[&current_node].left,
-                Direction::Right => self.nodes[&current_node].right,
-            };
+            let dir = self.directions[dir_index % self.directions.len()];
+            current_node = self.follow(current_node, dir)?;
             dir_index += 1;
             steps += 1;
         }
 
-        steps
+        Ok(steps)
     }
 
-    fn get_steps_to_all_ends(&self) -> u64 {
-        let mut steps = 0;
-        let mut current_nodes: Vec<Node> = self
-            .nodes
-            .keys()
-            .filter(|node| node.id.last() == Some(&'A'))
-            .copied()
-            .collect();
+    fn get_steps_to_all_ends(&self) -> Result<u64, String> {
+        let hits: Vec<HitTimes> = (0..self.graph.len())
+            .filter(|&node| self.graph.node(node).id.last() == Some(&'A'))
+            .map(|start| self.end_hits(start))
+            .try_collect()?;
+
+        utils::first_common_time(&hits)
+    }
 
-        // pre-initialize all the steps for starting node
-        let mut steps_per_start = vec![None; current_nodes.len()];
+    /// Every number of steps after which a walk from `start` is standing on an end node
+    fn end_hits(&self, start: usize) -> Result<HitTimes, String> {
+        // where the walk goes next only depends on the node and how far through the directions
+        // we are, so as soon as one of those pairs comes up again, the walk is in a cycle
+        let mut first_seen = HashMap::new();
+        let mut end_steps = Vec::new();
 
-        let mut dir_index = 0;
+        let mut node = start;
+        let mut steps = 0;
         loop {
-            if steps_per_start.iter().all(|steps| steps.is_some()) {
-                break;
+            let dir_index = steps as usize % self.directions.len();
+            if let Some(&cycle_start) = first_seen.get(&(node, dir_index)) {
+                let (once, repeating): (Vec<u64>, Vec<u64>) = end_steps
+                    .into_iter()
+                    .partition(|&end_step| end_step < cycle_start);
+                let offsets = repeating
+                    .into_iter()
+                    .map(|end_step| end_step - cycle_start)
+                    .collect();
+
+                return HitTimes::new(once, cycle_start, steps - cycle_start, offsets);
             }
+            first_seen.insert((node, dir_index), steps);
 
-            let dir = &self.directions[dir_index % self.directions.len()];
-            for (i, node) in current_nodes.iter_mut().enumerate() {
-                if node.id.last() == Some(&'Z') && steps_per_start[i].is_none() {
-                    steps_per_start[i] = Some(steps);
-                }
-                *node = match dir {
-                    Direction::Left => self.nodes[&node].left,
-                    Direction::Right => self.nodes[&node].right,
-                };
+            if self.graph.node(node).id.last() == Some(&'Z') {
+                end_steps.push(steps);
             }
-            dir_index += 1;
+            node = self.follow(node, self.directions[dir_index])?;
             steps += 1;
         }
-
-        let total_steps = steps_per_start
-            .into_iter()
-            .reduce(|a, b| Some(a.unwrap().lcm(&b.unwrap())))
-            .unwrap()
-            .unwrap();
-
-        total_steps
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Left,
     Right,
@@ -161,19 +216,51 @@ impl Node {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct NodeFork {
-    left: Node,
-    right: Node,
-}
-
-impl NodeFork {
-    fn parse_from_string(input: &str) -> Self {
-        let (left, right) = input[1..input.len() - 1].split_once(", ").unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        let map = Map::parse_from_string(
+            "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)",
+        )
+        .unwrap();
+        assert_eq!(map.get_steps_to_end(), Ok(6));
+
+        let map = Map::parse_from_string(
+            "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)",
+        )
+        .unwrap();
+        assert_eq!(map.get_steps_to_all_ends(), Ok(6));
+    }
 
-        Self {
-            left: Node::new(left),
-            right: Node::new(right),
-        }
+    #[test]
+    fn test_broken_networks() {
+        let map = Map::parse_from_string("L\n\nBBB = (ZZZ, ZZZ)").unwrap();
+        assert_eq!(
+            map.get_steps_to_end(),
+            Err("there's no node AAA".to_string())
+        );
+
+        // ZZZ is only ever named as a destination, so there's no way out of it
+        let map = Map::parse_from_string("LL\n\nAAA = (ZZZ, ZZZ)\nBBA = (ZZZ, ZZZ)").unwrap();
+        assert_eq!(
+            map.get_steps_to_all_ends(),
+            Err("node ZZZ has no way Left".to_string())
+        );
     }
 }
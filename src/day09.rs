@@ -1,10 +1,13 @@
 use std::fs;
 
-use crate::{utils, AdventError, ExclusivePart};
+use crate::{
+    utils::{self, Cursor, ParseError, Polynomial},
+    AdventError, ExclusivePart, RunContext,
+};
 
 const INPUT_FILE: &str = "./resources/day09_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -16,14 +19,11 @@ fn part_one() -> Result<String, AdventError> {
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let sequence_trees: Vec<_> = input
-        .lines()
-        .map(|line| SequenceTree::parse_from_str(line))
-        .collect();
+    let sequence_trees = utils::line_by_line(&input, SequenceTree::parse)?;
 
     let mut prediction_sum = 0;
     for tree in sequence_trees {
-        let prediction = tree.predict_next();
+        let prediction = tree.predict_next()?;
         prediction_sum += prediction;
     }
 
@@ -35,14 +35,11 @@ fn part_two() -> Result<String, AdventError> {
     let input =
         fs::read_to_string(INPUT_FILE).map_err(|err| AdventError::Other(err.to_string()))?;
 
-    let sequence_trees: Vec<_> = input
-        .lines()
-        .map(|line| SequenceTree::parse_from_str(line))
-        .collect();
+    let sequence_trees = utils::line_by_line(&input, SequenceTree::parse)?;
 
     let mut prediction_sum = 0;
     for tree in sequence_trees {
-        let prediction = tree.predict_previous();
+        let prediction = tree.predict_previous()?;
         prediction_sum += prediction;
     }
 
@@ -51,48 +48,28 @@ fn part_two() -> Result<String, AdventError> {
 
 #[derive(Debug)]
 struct SequenceTree {
-    layers: Vec<Vec<i64>>,
+    length: i64,
+    polynomial: Polynomial,
 }
 
 impl SequenceTree {
-    fn parse_from_str(input: &str) -> Self {
-        let nums = utils::integers_from_string::<i64>(input, " ");
-
-        let mut layers = Vec::new();
-        layers.push(nums);
-
-        while !layers.last().unwrap().iter().all(|i| *i == 0) {
-            let last_layer = layers.last().unwrap();
-
-            let mut diff_layer = Vec::new();
-            for i in 1..last_layer.len() {
-                let diff = last_layer[i] - last_layer[i - 1];
-                diff_layer.push(diff);
-            }
-
-            layers.push(diff_layer);
-        }
-
-        Self { layers }
+    fn parse(cursor: &mut Cursor) -> Result<Self, ParseError> {
+        let nums =
+            cursor.separated_list(|cursor| cursor.spaces(), |cursor| cursor.integer::<i64>())?;
+
+        // a sequence whose differences eventually all reach zero is a polynomial,
+        // which is exactly what the tree of differences finds
+        Ok(Self {
+            length: nums.len() as i64,
+            polynomial: Polynomial::fit_sequence(&nums),
+        })
     }
 
-    fn predict_next(&self) -> i64 {
-        let mut prediction = 0;
-        for i in (0..self.layers.len() - 1).rev() {
-            let this_layer = &self.layers[i];
-
-            prediction = this_layer.last().unwrap() + prediction;
-        }
-        prediction
+    fn predict_next(&self) -> Result<i64, String> {
+        self.polynomial.evaluate_integer(self.length)
     }
 
-    fn predict_previous(&self) -> i64 {
-        let mut prediction = 0;
-        for i in (0..self.layers.len() - 1).rev() {
-            let this_layer = &self.layers[i];
-
-            prediction = this_layer.first().unwrap() - prediction;
-        }
-        prediction
+    fn predict_previous(&self) -> Result<i64, String> {
+        self.polynomial.evaluate_integer(-1)
     }
 }
@@ -1,14 +1,17 @@
 use core::fmt;
-use std::{char, collections::HashSet, fs, io::Write};
+use std::{char, collections::HashSet, fs};
 
-use crate::{AdventError, ExclusivePart};
+use crate::{
+    utils::{GridPoint, Polygon},
+    AdventError, ExclusivePart, RunContext, Verbosity,
+};
 
 const INPUT_FILE: &str = "./resources/day10_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
@@ -24,13 +27,13 @@ fn part_one() -> Result<String, AdventError> {
     Ok(distance_from_start.to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
     let pipe_map = PipeMap::parse_from_str(&input)?;
 
-    let num_enclosed_tiles = pipe_map.find_num_enclosed_tiles(false)?;
+    let num_enclosed_tiles = pipe_map.find_num_enclosed_tiles(ctx)?;
 
     Ok(num_enclosed_tiles.to_string())
 }
@@ -259,13 +262,52 @@ impl PipeMap {
         }
     }
 
-    fn find_num_enclosed_tiles(&self, debug: bool) -> Result<u64, String> {
-        self.find_num_enclosed_tiles_flood_fill(debug)
+    fn find_num_enclosed_tiles(&self, ctx: &mut RunContext) -> Result<u64, String> {
+        ctx.solve_with(&[
+            ("polygon", &|ctx| self.find_num_enclosed_tiles_polygon(ctx)),
+            ("raycast", &|ctx| self.find_num_enclosed_tiles_raycast(ctx)),
+            ("flood-fill", &|ctx| {
+                self.find_num_enclosed_tiles_flood_fill(ctx)
+            }),
+        ])
     }
 
-    fn _find_num_enclosed_tiles_raycast(&self, debug: bool) -> Result<u64, String> {
+    fn find_num_enclosed_tiles_polygon(&self, ctx: &mut RunContext) -> Result<u64, String> {
         let (path_coords, _winding) = self.get_path_coords_and_winding()?;
 
+        // the pipe loop is a lattice polygon, so Pick's theorem counts what's inside it
+        let polygon: Polygon<GridPoint> = path_coords
+            .iter()
+            .map(|coords| GridPoint::from((coords.x, coords.y)))
+            .collect();
+
+        if ctx.is_enabled(Verbosity::Trace) {
+            let mut flooded = HashSet::new();
+            for x in 0..self.grid.len() {
+                for y in 0..self.grid[x].len() {
+                    if polygon.strictly_contains(&(x, y).into()) {
+                        flooded.insert(Coordinates {
+                            x: x as i64,
+                            y: y as i64,
+                        });
+                    }
+                }
+            }
+            ctx.trace(self.debug_grid_string(&path_coords, &flooded));
+        }
+
+        let interior_points = polygon
+            .interior_points()
+            .ok_or("pipe loop doesn't enclose any area")?;
+
+        Ok(interior_points as u64)
+    }
+
+    fn find_num_enclosed_tiles_raycast(&self, ctx: &mut RunContext) -> Result<u64, String> {
+        let (path_coords, _winding) = self.get_path_coords_and_winding()?;
+        let (start_coords, start_tile) = self.find_start_and_directions()?;
+        let path: HashSet<Coordinates> = path_coords.iter().copied().collect();
+
         // raycast from all coords
         let mut flooded = HashSet::new();
         for x in 0..self.grid.len() {
@@ -278,7 +320,7 @@ impl PipeMap {
 
                 // ignore if this coordinate is in the path
                 // since it can't possibly be enclosed
-                if path_coords.contains(&coords) {
+                if path.contains(&coords) {
                     continue;
                 }
 
@@ -291,9 +333,20 @@ impl PipeMap {
                         y: ny as i64,
                     };
 
-                    let cast_tile = self.get_or_ground(cast_coords);
+                    let cast_tile = if cast_coords == start_coords {
+                        start_tile
+                    } else {
+                        self.get_or_ground(cast_coords)
+                    };
 
-                    if path_coords.contains(&cast_coords) && cast_tile != Tile::NorthSouth {
+                    // only count the path where it heads east. where it runs along the ray, the
+                    // bends at either end then count twice if it comes back out on the side it
+                    // went in (┌ then └), which cancels out, but once if it crosses (┌ then ┘)
+                    let heads_east = matches!(
+                        cast_tile,
+                        Tile::EastWest | Tile::NorthEast | Tile::SouthEast
+                    );
+                    if heads_east && path.contains(&cast_coords) {
                         intersections += 1;
                     }
                 }
@@ -305,8 +358,8 @@ impl PipeMap {
             }
         }
 
-        if debug {
-            self.debug_print_grid(&path_coords, &flooded)?;
+        if ctx.is_enabled(Verbosity::Trace) {
+            ctx.trace(self.debug_grid_string(&path_coords, &flooded));
         }
 
         let num_enclosed_tiles = flooded.len();
@@ -314,7 +367,7 @@ impl PipeMap {
         Ok(num_enclosed_tiles as u64)
     }
 
-    fn find_num_enclosed_tiles_flood_fill(&self, debug: bool) -> Result<u64, String> {
+    fn find_num_enclosed_tiles_flood_fill(&self, ctx: &mut RunContext) -> Result<u64, String> {
         let (path_coords, winding) = self.get_path_coords_and_winding()?;
 
         // get bordering interior coords
@@ -428,8 +481,8 @@ impl PipeMap {
             self.flood_fill(coords, &path_coords, &mut flooded);
         }
 
-        if debug {
-            self.debug_print_grid(&path_coords, &flooded)?;
+        if ctx.is_enabled(Verbosity::Trace) {
+            ctx.trace(self.debug_grid_string(&path_coords, &flooded));
         }
 
         let num_enclosed_tiles = flooded.len();
@@ -437,14 +490,13 @@ impl PipeMap {
         Ok(num_enclosed_tiles as u64)
     }
 
-    fn debug_print_grid(
+    /// The pipe loop drawn in box-drawing characters, with flooded tiles marked `*`
+    fn debug_grid_string(
         &self,
         path_coords: &Vec<Coordinates>,
         flooded: &HashSet<Coordinates>,
-    ) -> Result<(), String> {
-        let mut debug_file =
-            fs::File::create("./day10_debug.txt").map_err(|err| err.to_string())?;
-
+    ) -> String {
+        let mut grid_string = String::new();
         for y in 0..self.grid[0].len() {
             for x in 0..self.grid.len() {
                 let coords = Coordinates {
@@ -459,17 +511,17 @@ impl PipeMap {
                 let else_str = " ";
 
                 if path_coords.contains(&coords) {
-                    write!(debug_file, "{}", path_str).map_err(|err| err.to_string())?;
+                    grid_string.push_str(&path_str);
                 } else if flooded.contains(&coords) {
-                    write!(debug_file, "{}", flood_str).map_err(|err| err.to_string())?;
+                    grid_string.push_str(flood_str);
                 } else {
-                    write!(debug_file, "{}", else_str).map_err(|err| err.to_string())?;
+                    grid_string.push_str(else_str);
                 }
             }
-            write!(debug_file, "\n").map_err(|err| err.to_string())?;
+            grid_string.push('\n');
         }
 
-        Ok(())
+        grid_string
     }
 }
 
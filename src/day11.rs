@@ -2,12 +2,12 @@ use std::fs;
 
 use crate::{
     utils::{Grid, GridEntry, GridPoint},
-    AdventError, ExclusivePart,
+    AdventError, ExclusivePart, RunContext,
 };
 
 const INPUT_FILE: &str = "./resources/day11_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -2,43 +2,42 @@ use std::{collections::HashMap, fs};
 
 use itertools::Itertools;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{AdventError, ExclusivePart, RunContext};
 
 const INPUT_FILE: &str = "./resources/day12_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
-    let debug = false;
-    let brute_force = false;
-
     let spring_rows = input
         .lines()
         .map(|line| SpringRow::parse(line))
         .collect::<Result<Vec<SpringRow>, String>>()?;
 
-    let mut sum_of_possible_arrangements = 0;
-    for (_i, row) in spring_rows.iter().enumerate() {
-        sum_of_possible_arrangements += row.possible_arrangements_count(debug, brute_force);
-    }
+    let sum_of_possible_arrangements = ctx.solve_with::<_, String>(&[
+        ("memoization", &|ctx| {
+            Ok(sum_of_possible_arrangements(&spring_rows, ctx, false))
+        }),
+        ("brute-force", &|ctx| {
+            Ok(sum_of_possible_arrangements(&spring_rows, ctx, true))
+        }),
+    ])?;
 
     Ok(sum_of_possible_arrangements.to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
-    let debug = false;
-    let brute_force = false;
     let times = 5;
 
     let spring_rows = input
@@ -51,14 +50,28 @@ fn part_two() -> Result<String, AdventError> {
         .map(|row| row.into_unfolded(times))
         .collect();
 
-    let mut sum_of_possible_arrangements = 0;
-    for (_i, row) in spring_rows.iter().enumerate() {
-        sum_of_possible_arrangements += row.possible_arrangements_count(debug, brute_force);
-    }
+    // the unfolded rows are far too long to brute force
+    let sum_of_possible_arrangements = ctx.solve_with::<_, String>(&[("memoization", &|ctx| {
+        Ok(sum_of_possible_arrangements(&spring_rows, ctx, false))
+    })])?;
 
     Ok(sum_of_possible_arrangements.to_string())
 }
 
+fn sum_of_possible_arrangements(
+    spring_rows: &[SpringRow],
+    ctx: &mut RunContext,
+    brute_force: bool,
+) -> u64 {
+    let mut sum_of_possible_arrangements = 0;
+    for row in spring_rows {
+        let arrangements_count = row.possible_arrangements_count(ctx, brute_force);
+        ctx.debug(format_args!("{}: {} arrangements", row, arrangements_count));
+        sum_of_possible_arrangements += arrangements_count;
+    }
+    sum_of_possible_arrangements
+}
+
 #[derive(Debug, Clone)]
 struct SpringRow {
     springs: Vec<SpringCondition>,
@@ -107,19 +120,19 @@ impl SpringRow {
         }
     }
 
-    fn possible_arrangements_count(&self, debug: bool, brute_force: bool) -> u64 {
+    fn possible_arrangements_count(&self, ctx: &mut RunContext, brute_force: bool) -> u64 {
         if brute_force {
             self.possible_arrangements_brute_force().len() as u64
         } else {
-            self.possible_arrangements_count_progressive_memoization(debug)
+            self.possible_arrangements_count_progressive_memoization(ctx)
         }
     }
 
-    fn possible_arrangements_count_progressive_memoization(&self, debug: bool) -> u64 {
+    fn possible_arrangements_count_progressive_memoization(&self, ctx: &mut RunContext) -> u64 {
         let mut memo_table = HashMap::new();
         self.possible_arrangements_at(
             0,
-            debug,
+            ctx,
             0,
             self.damaged_spring_groups.clone(),
             &mut memo_table,
@@ -166,21 +179,19 @@ impl SpringRow {
     fn possible_arrangements_at(
         &self,
         level: usize,
-        debug: bool,
+        ctx: &mut RunContext,
         index: usize,
         groups: Vec<u64>,
         memo_table: &mut HashMap<(usize, usize), u64>,
     ) -> u64 {
         let indent = 2;
 
-        if debug {
-            println!(
-                "{}checking {} with {} groups",
-                " ".repeat(level),
-                index,
-                groups.len()
-            );
-        }
+        ctx.trace(format_args!(
+            "{:level$}checking {} with {} groups",
+            "",
+            index,
+            groups.len()
+        ));
         if groups.len() == 0 {
             // we have nothing left to place!
             if index == 0
@@ -192,24 +203,18 @@ impl SpringRow {
             {
                 // if there are any damaged springs left in the list, but there's nothing left
                 // for us to place, we can't make an arrangement
-                if debug {
-                    println!(
-                        "{}nothing left to place at {}, but there's still '#'! returning 0",
-                        " ".repeat(level),
-                        index
-                    );
-                }
+                ctx.trace(format_args!(
+                    "{:level$}nothing left to place at {}, but there's still '#'! returning 0",
+                    "", index
+                ));
                 return 0;
             } else {
                 // if there AREN'T any damaged springs left in the list, we return
                 // 1 as a sort-of "base case", indicating that whatever arrangement led us here is valid
-                if debug {
-                    println!(
-                        "{}nothing left to place at {}. we're done here! returning 1",
-                        " ".repeat(level),
-                        index
-                    );
-                }
+                ctx.trace(format_args!(
+                    "{:level$}nothing left to place at {}. we're done here! returning 1",
+                    "", index
+                ));
                 return 1;
             }
         }
@@ -217,9 +222,10 @@ impl SpringRow {
         if index >= self.springs.len() {
             // we're off the row!
             // there's no possible arrangement that we can make
-            if debug {
-                println!("{}off the row at {}! returning 0", " ".repeat(level), index);
-            }
+            ctx.trace(format_args!(
+                "{:level$}off the row at {}! returning 0",
+                "", index
+            ));
             return 0;
         }
 
@@ -228,15 +234,11 @@ impl SpringRow {
             // if we're operational, there's no info to learn here
             // so just return whatever arrangements are available starting 1 after us
             let next_arrangements =
-                self.possible_arrangements_at(level + indent, debug, index + 1, groups, memo_table);
-            if debug {
-                println!(
-                    "{}found '.' at {}, using next arrangements ({})",
-                    " ".repeat(level),
-                    index,
-                    next_arrangements
-                );
-            }
+                self.possible_arrangements_at(level + indent, ctx, index + 1, groups, memo_table);
+            ctx.trace(format_args!(
+                "{:level$}found '.' at {}, using next arrangements ({})",
+                "", index, next_arrangements
+            ));
             return next_arrangements;
         }
 
@@ -244,15 +246,13 @@ impl SpringRow {
 
         // but first, let's see if we've already calculated this arrangement
         if let Some(arrangements_count) = memo_table.get(&(index, groups.len())) {
-            if debug {
-                println!(
-                    "{}found {} arrangements at {} with {} groups (from cache)",
-                    " ".repeat(level),
-                    arrangements_count,
-                    index,
-                    groups.len()
-                );
-            }
+            ctx.trace(format_args!(
+                "{:level$}found {} arrangements at {} with {} groups (from cache)",
+                "",
+                arrangements_count,
+                index,
+                groups.len()
+            ));
             return *arrangements_count;
         }
 
@@ -283,7 +283,7 @@ impl SpringRow {
         let consume_arrangements_count = if can_consume {
             self.possible_arrangements_at(
                 level + indent,
-                debug,
+                ctx,
                 index + first_group_len + 1,
                 groups[1..].to_vec(),
                 memo_table,
@@ -298,7 +298,7 @@ impl SpringRow {
         let skip_arrangements_count = if spring == SpringCondition::Unknown {
             self.possible_arrangements_at(
                 level + indent,
-                debug,
+                ctx,
                 index + 1,
                 groups.clone(),
                 memo_table,
@@ -308,28 +308,24 @@ impl SpringRow {
         };
 
         let arrangements_count = consume_arrangements_count + skip_arrangements_count;
-        if debug {
-            println!(
-                "{}found {} arrangements at {} with {} groups ({} consume + {} skip)",
-                " ".repeat(level),
-                arrangements_count,
-                index,
-                groups.len(),
-                consume_arrangements_count,
-                skip_arrangements_count
-            );
-        }
+        ctx.trace(format_args!(
+            "{:level$}found {} arrangements at {} with {} groups ({} consume + {} skip)",
+            "",
+            arrangements_count,
+            index,
+            groups.len(),
+            consume_arrangements_count,
+            skip_arrangements_count
+        ));
 
         // now that we've calculated this arrangement, we can cache it
-        if debug {
-            println!(
-                "{}saving {} arrangements at {} with {} groups",
-                " ".repeat(level),
-                arrangements_count,
-                index,
-                groups.len()
-            );
-        }
+        ctx.trace(format_args!(
+            "{:level$}saving {} arrangements at {} with {} groups",
+            "",
+            arrangements_count,
+            index,
+            groups.len()
+        ));
         memo_table.insert((index, groups.len()), arrangements_count);
 
         // and we're done
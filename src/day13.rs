@@ -1,13 +1,13 @@
 use std::fs;
 
 use crate::{
-    utils::{Grid, GridPoint},
-    AdventError, ExclusivePart,
+    utils::{self, Grid, GridPoint},
+    AdventError, ExclusivePart, RunContext,
 };
 
 const INPUT_FILE: &str = "./resources/day13_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -18,9 +18,9 @@ fn part_one() -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
-    let patterns = input
-        .split("\n\n")
-        .map(|pattern_str| Pattern::parse(pattern_str))
+    let patterns = utils::sections(&input)
+        .iter()
+        .map(|section| section.parse(Pattern::parse))
         .collect::<Result<Vec<Pattern>, String>>()?;
 
     let mut lines_of_symmetry = Vec::new();
@@ -48,9 +48,9 @@ fn part_two() -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
-    let patterns = input
-        .split("\n\n")
-        .map(|pattern_str| Pattern::parse(pattern_str))
+    let patterns = utils::sections(&input)
+        .iter()
+        .map(|section| section.parse(Pattern::parse))
         .collect::<Result<Vec<Pattern>, String>>()?;
 
     let mut lines_of_symmetry = Vec::new();
@@ -135,7 +135,6 @@ impl Pattern {
             .get(0)
             .expect("couldn't find single exclusive existing line of symmetry");
 
-        // println!("existing line of symmetry: {:?}", existing_line_of_symmetry);
         let mut lines_of_symmetry = Vec::new();
         for x in 0..self.terrain.width() {
             for y in 0..self.terrain.height() {
@@ -161,12 +160,6 @@ impl Pattern {
                 let new_pattern = Pattern::from_grid(potential_smudge_grid);
 
                 let new_pattern_lines_of_symmetry = new_pattern.find_lines_of_symmetry();
-                // if x == 4 && y == 0 {
-                //     println!("outputting for x=0, y=4");
-                //     println!("new pattern: \n{}", new_pattern);
-                //     println!("new line of symmetry: {:?}", new_pattern_line_of_symmetry);
-                //     println!("old line of symmetry: {:?}", existing_line_of_symmetry);
-                // }
                 for new_los in new_pattern_lines_of_symmetry {
                     if new_los != existing_line_of_symmetry {
                         lines_of_symmetry.push(new_los);
@@ -214,12 +207,7 @@ impl Pattern {
             if true_row != last_matching_column {
                 return false;
             }
-            // println!(
-            //     "matched column {:?} against last ({:?})",
-            //     true_row, last_matching_column
-            // );
         }
-        // println!("found a symmetric column at x = {}", x);
         true
     }
 }
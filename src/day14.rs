@@ -4,13 +4,13 @@ use std::{
 };
 
 use crate::{
-    utils::{Grid, GridPoint},
-    AdventError, ExclusivePart,
+    utils::{self, Grid, GridPoint},
+    AdventError, ExclusivePart, RunContext,
 };
 
 const INPUT_FILE: &str = "./resources/day14_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -35,8 +35,7 @@ fn part_two() -> Result<String, AdventError> {
     let cycles = 1_000_000_000;
 
     let platform = Platform::parse(&input)?;
-    let mut platform_cycler = PlatformCycler::new(platform);
-    let cycled_platform = platform_cycler.cycle_n_times(cycles)?;
+    let cycled_platform = utils::try_simulate_n(platform, Platform::cycle, cycles)?;
 
     let rounded_load = cycled_platform.load_from_rounded_rocks();
     Ok(rounded_load.to_string())
@@ -243,36 +242,6 @@ impl Platform {
     }
 }
 
-struct PlatformCycler {
-    platform_progression: Vec<Platform>,
-}
-
-impl PlatformCycler {
-    fn new(starting_platform: Platform) -> Self {
-        Self {
-            platform_progression: vec![starting_platform],
-        }
-    }
-
-    fn cycle_n_times(&mut self, n: usize) -> Result<Platform, String> {
-        for _ in 0..n {
-            let current_platform = self.platform_progression.last().unwrap();
-            let next_cycled_platform = current_platform.cycle()?;
-            if let Some(pos) = self
-                .platform_progression
-                .iter()
-                .position(|p| p.clone() == next_cycled_platform)
-            {
-                let cycle_length = self.platform_progression.len() - pos;
-                let cycle_offset = ((n - pos) % cycle_length) + pos;
-                return Ok(self.platform_progression[cycle_offset].clone());
-            }
-            self.platform_progression.push(next_cycled_platform);
-        }
-        Ok(self.platform_progression.last().unwrap().clone())
-    }
-}
-
 impl std::fmt::Display for Platform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (y, row) in self.spaces.rows_iter().enumerate() {
@@ -1,10 +1,10 @@
 use std::fs;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{AdventError, ExclusivePart, RunContext};
 
 const INPUT_FILE: &str = "./resources/day15_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -3,20 +3,20 @@ use std::{collections::HashSet, fs};
 use itertools::Itertools;
 
 use crate::{
-    utils::{Grid, GridEntry, GridPoint},
-    AdventError, ExclusivePart,
+    utils::{span, Grid, GridEntry, GridPoint},
+    AdventError, ExclusivePart, RunContext, Verbosity,
 };
 
 const INPUT_FILE: &str = "./resources/day16_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
@@ -27,12 +27,16 @@ fn part_one() -> Result<String, AdventError> {
         direction: BeamDirection::East,
     })?;
 
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(resolved_contraption.energized_tiles_string());
+    }
+
     let energized_tiles = resolved_contraption.energized_tiles();
 
     Ok(energized_tiles.len().to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
@@ -40,10 +44,14 @@ fn part_two() -> Result<String, AdventError> {
 
     let starting_beam = contraption.find_ideal_starting_beam()?;
 
-    // println!("starting beam: {:?}", starting_beam);
+    ctx.debug(format_args!("starting beam: {:?}", starting_beam));
 
     let resolved_contraption = contraption.resolve_beams_starting_from(starting_beam)?;
 
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(resolved_contraption.energized_tiles_string());
+    }
+
     let energized_tiles = resolved_contraption.energized_tiles();
 
     Ok(energized_tiles.len().to_string())
@@ -56,6 +64,8 @@ struct Contraption {
 
 impl Contraption {
     fn parse(input: &str) -> Result<Self, String> {
+        let _span = span!("parse");
+
         let height = input.lines().count();
         let width = input.lines().next().unwrap().len();
 
@@ -76,6 +86,8 @@ impl Contraption {
     }
 
     fn find_ideal_starting_beam(&self) -> Result<BeamData, String> {
+        let _span = span!("ideal starting beam");
+
         let edge_beams = self.get_all_possible_edge_beams();
         let mut optimal_beam = None;
         let mut optimal_energized_tiles = 0;
@@ -123,6 +135,8 @@ impl Contraption {
         &self,
         starting_beam: BeamData,
     ) -> Result<ResolvedContraption, String> {
+        let _span = span!("resolve beams");
+
         let tile_data = self
             .tiles
             .clone()
@@ -146,7 +160,6 @@ impl Contraption {
             .insert(starting_beam.direction);
 
         while !to_resolve.is_empty() {
-            // println!("to_resolve: {:?}", to_resolve);
             let active_beam = *to_resolve.iter().next().unwrap();
 
             let produced_beams = active_beam.resolve(&resolved_contraption.tiles);
@@ -187,7 +200,7 @@ impl ResolvedContraption {
             .collect_vec()
     }
 
-    fn _energized_tiles_string(&self) -> String {
+    fn energized_tiles_string(&self) -> String {
         let mut s = String::new();
         for (y, row) in self.tiles.rows_iter().enumerate() {
             for tile in row {
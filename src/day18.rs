@@ -3,15 +3,13 @@ use std::fs;
 use itertools::Itertools;
 
 use crate::{
-    utils::{
-        CardinalDirection, Grid, GridEntry, GridPoint, OrdinalDirection, RelativeDirection, Winding,
-    },
-    AdventError, ExclusivePart,
+    utils::{CardinalDirection, GridEntry, GridLike, GridPoint, Polygon, SparseGrid, Winding},
+    AdventError, ExclusivePart, RunContext,
 };
 
 const INPUT_FILE: &str = "./resources/day18_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -39,21 +37,21 @@ fn part_two() -> Result<String, AdventError> {
 
     let dig_plan = DigPlan::parse(&input, true)?;
 
-    let num_tiles = dig_plan.shoelace_area()?;
+    let num_tiles = dig_plan.lagoon_area()?;
 
     Ok(num_tiles.to_string())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Lagoon {
-    map: Grid<TerrainTile>,
+    map: SparseGrid<TerrainTile>,
     trench_points: Vec<GridPoint>,
     winding: Winding,
 }
 
 impl Lagoon {
     fn parse(dig_plan: &DigPlan) -> Result<Self, String> {
-        let mut map = Grid::new_empty(1, 1);
+        let mut map = SparseGrid::new();
         let mut trench_points = Vec::new();
 
         // start in the top left, in a dug out cube
@@ -62,36 +60,15 @@ impl Lagoon {
         trench_points.push(current_pos);
 
         // dig out the cubes according to the plan
-        let previous_direction: Option<CardinalDirection> = None;
-        let mut left_turns = 0;
-        let mut right_turns = 0;
-        for (_i, step) in dig_plan.steps.iter().enumerate() {
-            // println!("starting step {}: {:?} as {}", i + 1, step, current_pos);
-
-            if let Some(previous_direction) = previous_direction {
-                match previous_direction.relative_direction_to(&step.direction) {
-                    RelativeDirection::Left => {
-                        left_turns += 1;
-                    }
-                    RelativeDirection::Right => {
-                        right_turns += 1;
-                    }
-                    _ => {}
-                }
-            }
-
+        for step in dig_plan.steps.iter() {
             for _ in 0..step.distance {
                 current_pos = current_pos.neighbor_in_direction(step.direction);
-                map.set_expand(&current_pos, TerrainTile::Trench)?;
+                map.set(&current_pos, TerrainTile::Trench)?;
                 trench_points.push(current_pos);
             }
         }
 
-        let winding = if left_turns > right_turns {
-            Winding::Left
-        } else {
-            Winding::Right
-        };
+        let winding = dig_plan.winding;
 
         Ok(Lagoon {
             map,
@@ -181,9 +158,6 @@ impl DigPlan {
         let mut corners = Vec::new();
 
         let mut current_position: GridPoint = (0, 0).into();
-        let mut previous_direction: Option<CardinalDirection> = None;
-        let mut left_turns = 0;
-        let mut right_turns = 0;
         for line in input.lines() {
             // parse the actual step
             let step = DigPlanStep::parse(line, extract_from_color)?;
@@ -193,28 +167,12 @@ impl DigPlan {
             current_position = current_position
                 .neighbor_in_direction_distance(step.direction, step.distance as i64);
 
-            // check for winding
-            if let Some(previous_direction) = previous_direction {
-                match previous_direction.relative_direction_to(&step.direction) {
-                    RelativeDirection::Left => {
-                        left_turns += 1;
-                    }
-                    RelativeDirection::Right => {
-                        right_turns += 1;
-                    }
-                    _ => {}
-                }
-            }
-            previous_direction = Some(step.direction);
-
             steps.push(step);
         }
 
-        let winding = if left_turns > right_turns {
-            Winding::Left
-        } else {
-            Winding::Right
-        };
+        let winding = Polygon::new(corners.clone())
+            .winding()
+            .ok_or("dig plan doesn't enclose any area")?;
 
         Ok(DigPlan {
             steps,
@@ -223,72 +181,11 @@ impl DigPlan {
         })
     }
 
-    fn true_corners(&self) -> Result<Vec<GridPoint>, String> {
-        let mut true_corners = Vec::new();
-
-        let relative_winding: RelativeDirection = self.winding.into();
-
-        for i in 0..self.corners.len() {
-            let previous_corner = if i == 0 {
-                &self.corners[self.corners.len() - 1]
-            } else {
-                &self.corners[i - 1]
-            };
-            let this_corner = &self.corners[i];
-            let next_corner = &self.corners[(i + 1) % self.corners.len()];
-
-            // direction to neighboring corners
-            // this determines what kind of corner WE are
-            let direction_backwards = this_corner.direction_to_orthogonal(previous_corner)?;
-            let direction_forwards = this_corner.direction_to_orthogonal(next_corner)?;
-
-            // this is which way we turned here.
-            let relative_direction = direction_backwards
-                .opposite()
-                .relative_direction_to(&direction_forwards);
-
-            let mut outside_corner = direction_backwards.halfway_to(&direction_forwards)?;
-
-            // if the way we turned matches the winding, it means we turned INWARDS,
-            // so we have to shift outwards to include the boundary
-            if relative_direction == relative_winding {
-                outside_corner = outside_corner.opposite();
-            }
-
-            let mut true_corner = this_corner.clone();
-            match outside_corner {
-                OrdinalDirection::NorthEast => {
-                    true_corner.x += 1;
-                    true_corner.y -= 1;
-                }
-                OrdinalDirection::SouthEast => {
-                    true_corner.x += 1;
-                }
-                OrdinalDirection::SouthWest => {}
-                OrdinalDirection::NorthWest => {
-                    true_corner.y -= 1;
-                }
-            }
-
-            true_corners.push(true_corner);
-        }
-        Ok(true_corners)
-    }
-
-    fn shoelace_area(&self) -> Result<u64, String> {
-        let corners = self.true_corners()?;
-
-        let mut area: i64 = 0;
-        for i in 0..corners.len() {
-            let j = (i + 1) % corners.len();
-
-            let a = corners[i];
-            let b = corners[j];
-
-            area += a.x * b.y - a.y * b.x;
-        }
-
-        Ok((area.abs() / 2) as u64)
+    fn lagoon_area(&self) -> Result<u128, String> {
+        // the trench itself is a whole cell wide, so its boundary counts towards the area too
+        Polygon::new(self.corners.clone())
+            .area_including_boundary()
+            .ok_or("dig plan doesn't enclose any area".to_string())
     }
 }
 
@@ -1,15 +1,17 @@
-use std::{cmp, collections::HashMap, fs};
+use std::{cmp, collections::HashMap, fs, ops};
 
 use itertools::Itertools;
 
 use crate::{
-    utils::{Interval, SortedDisjointIntervalList},
-    AdventError, ExclusivePart,
+    utils::{self, Cursor, DisjointBoxes, HyperRect, ParseError, Section},
+    AdventError, ExclusivePart, RunContext,
 };
 
 const INPUT_FILE: &str = "./resources/day19_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+const RATINGS: ops::RangeInclusive<i64> = 1..=4000;
+
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -20,14 +22,11 @@ fn part_one() -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
-    let (workflows_str, parts_str) = input.split_once("\n\n").unwrap();
+    let (workflows_section, parts_section) = split_sections(&input)?;
 
-    let workflows: Workflows = Workflows::parse(workflows_str)?;
+    let workflows = Workflows::parse(&workflows_section)?;
 
-    let parts: Vec<Part> = parts_str
-        .lines()
-        .map(|line| Part::parse(line))
-        .try_collect()?;
+    let parts = parts_section.lines(Part::parse)?;
 
     let accepted_parts = workflows.accepted_from(&parts)?;
 
@@ -43,45 +42,53 @@ fn part_two() -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
-    let (workflows_str, _) = input.split_once("\n\n").unwrap();
-
-    let workflows: Workflows = Workflows::parse(workflows_str)?;
+    let (workflows_section, _) = split_sections(&input)?;
 
-    // let parts: Vec<Part> = parts_str
-    //     .lines()
-    //     .map(|line| Part::parse(line))
-    //     .try_collect()?;
+    let workflows = Workflows::parse(&workflows_section)?;
 
     let accepted_part_ranges = workflows.sweep_accepted_ranges()?;
 
-    let mut total_options = 0;
-    for part_range in accepted_part_ranges {
-        let options_count = part_range.combination_counts();
-        // println!("{}", options_count);
-        total_options += options_count;
-    }
+    // the accepted ranges never overlap, but collecting them into a disjoint set makes sure of it
+    let total_options = accepted_part_ranges
+        .into_iter()
+        .map(|part_range| part_range.ratings)
+        .collect::<DisjointBoxes<4>>()
+        .volume()
+        .ok_or("too many combinations of ratings to count")?;
 
     Ok(total_options.to_string())
 }
 
+/// The workflows, and then the parts, which are separated by a blank line
+fn split_sections(input: &str) -> Result<(Section<'_>, Section<'_>), String> {
+    match utils::sections(input)[..] {
+        [workflows, parts] => Ok((workflows, parts)),
+        ref sections => Err(format!(
+            "expected a section of workflows and a section of parts, found {} sections",
+            sections.len()
+        )),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Workflows {
     workflows: HashMap<String, Rules>,
 }
 
 impl Workflows {
-    fn parse(input: &str) -> Result<Workflows, String> {
-        let mut workflows = HashMap::new();
-        for line in input.lines() {
-            let (name, rules_str) = line.split_once("{").unwrap();
-            let rules_str = &rules_str[..rules_str.len() - 1];
-
-            let rules = Rules::parse(rules_str)?;
-
-            workflows.insert(name.to_string(), rules);
-        }
-
-        Ok(Workflows { workflows })
+    /// One workflow per line, like `px{a<2006:qkq,m>2090:A,rfg}`
+    fn parse(section: &Section) -> Result<Workflows, String> {
+        let workflows = section.lines(|cursor| {
+            let name = cursor.identifier()?;
+            cursor.expect_literal("{")?;
+            let rules = Rules::parse(cursor)?;
+            cursor.expect_literal("}")?;
+            Ok((name.to_string(), rules))
+        })?;
+
+        Ok(Workflows {
+            workflows: workflows.into_iter().collect(),
+        })
     }
 
     fn accepted_from(&self, parts: &[Part]) -> Result<Vec<Part>, String> {
@@ -165,11 +172,8 @@ struct Rules {
 }
 
 impl Rules {
-    fn parse(input: &str) -> Result<Rules, String> {
-        let mut rules = Vec::new();
-        for rule_str in input.split(',') {
-            rules.push(Rule::parse(rule_str)?);
-        }
+    fn parse(cursor: &mut Cursor) -> Result<Rules, ParseError> {
+        let rules = cursor.separated_list(|cursor| cursor.expect_literal(","), Rule::parse)?;
         Ok(Rules { rules })
     }
 
@@ -187,33 +191,41 @@ impl Rules {
         let mut rejected = Vec::new();
         let mut to_resolve = Vec::new();
 
-        let mut current_part = part.clone();
+        let mut current_parts = vec![part.clone()];
         for rule in &self.rules {
-            let (passed, failed) = if rule.condition.is_none() {
-                (current_part.clone(), None)
-            } else {
-                let (p, f) = current_part.split_range(&rule.condition.unwrap());
-                (p, Some(f))
+            let (passed, failed) = match &rule.condition {
+                None => (current_parts, Vec::new()),
+                Some(condition) => {
+                    let mut passed = Vec::new();
+                    let mut failed = Vec::new();
+                    for current_part in &current_parts {
+                        let (p, f) = current_part.split_range(condition);
+                        passed.extend(p);
+                        failed.extend(f);
+                    }
+                    (passed, failed)
+                }
             };
 
-            match &rule.destination {
-                RuleResult::Finalize(FinalRuleResult::Accept) => {
-                    accepted.push(passed);
-                }
-                RuleResult::Finalize(FinalRuleResult::Reject) => {
-                    rejected.push(passed);
+            for passed in passed {
+                match &rule.destination {
+                    RuleResult::Finalize(FinalRuleResult::Accept) => {
+                        accepted.push(passed);
+                    }
+                    RuleResult::Finalize(FinalRuleResult::Reject) => {
+                        rejected.push(passed);
+                    }
+                    RuleResult::SendToWorkflow(workflow) => to_resolve.push(Next {
+                        workflow_name: workflow.clone(),
+                        part: passed,
+                    }),
                 }
-                RuleResult::SendToWorkflow(workflow) => to_resolve.push(Next {
-                    workflow_name: workflow.clone(),
-                    part: passed,
-                }),
             }
 
-            if let Some(failed) = failed {
-                current_part = failed;
-            } else {
+            if failed.is_empty() {
                 break;
             }
+            current_parts = failed;
         }
 
         Ok(RulesProcessRangeResult {
@@ -231,26 +243,24 @@ struct Rule {
 }
 
 impl Rule {
-    fn parse(input: &str) -> Result<Self, String> {
-        match input.split_once(':') {
-            Some((condition, destination)) => {
-                let condition = Condition::parse(condition)?;
-                let destination = RuleResult::parse(destination)?;
-
-                Ok(Rule {
-                    condition: Some(condition),
-                    destination,
-                })
-            }
-            None => {
-                let destination = RuleResult::parse(input)?;
+    /// Either `condition:destination`, or just a destination for the last rule
+    fn parse(cursor: &mut Cursor) -> Result<Self, ParseError> {
+        let rule = cursor.rest().split([',', '}']).next().unwrap_or_default();
+        let has_condition = rule.contains(':');
+
+        let condition = if has_condition {
+            let condition = Condition::parse(cursor)?;
+            cursor.expect_literal(":")?;
+            Some(condition)
+        } else {
+            None
+        };
+        let destination = RuleResult::parse(cursor)?;
 
-                Ok(Rule {
-                    condition: None,
-                    destination,
-                })
-            }
-        }
+        Ok(Rule {
+            condition,
+            destination,
+        })
     }
 }
 
@@ -262,26 +272,24 @@ struct Condition {
 }
 
 impl Condition {
-    fn parse(input: &str) -> Result<Condition, String> {
-        if input.len() < 3 {
-            return Err(format!("invalid condition (too short): {}", input));
+    fn parse(cursor: &mut Cursor) -> Result<Condition, ParseError> {
+        let start = *cursor;
+        let name = cursor.take_while(|c| c.is_ascii_lowercase());
+        let category = match name.chars().exactly_one() {
+            Ok(category) => PartCategory::parse(category),
+            Err(_) => Err(format!("invalid part category: {}", name)),
         }
-
-        let mut chars = input.chars();
-
-        let category = PartCategory::parse(chars.next().unwrap())?;
-
-        let ordering = match chars.next().unwrap() {
-            '>' => cmp::Ordering::Greater,
-            '<' => cmp::Ordering::Less,
-            _ => {
-                return Err(format!("invalid condition (bad comparator): {}", input));
-            }
+        .map_err(|err| start.error(err))?;
+
+        let ordering = if cursor.eat_literal(">") {
+            cmp::Ordering::Greater
+        } else if cursor.eat_literal("<") {
+            cmp::Ordering::Less
+        } else {
+            return Err(cursor.error("expected '<' or '>'"));
         };
 
-        let value = input[2..]
-            .parse::<i64>()
-            .map_err(|err| format!("invalid condition (bad value): {}: {}", input, err))?;
+        let value = cursor.integer::<i64>()?;
 
         Ok(Condition {
             category,
@@ -304,8 +312,8 @@ enum RuleResult {
 }
 
 impl RuleResult {
-    fn parse(input: &str) -> Result<RuleResult, String> {
-        match input {
+    fn parse(cursor: &mut Cursor) -> Result<RuleResult, ParseError> {
+        match cursor.identifier()? {
             "A" => Ok(RuleResult::Finalize(FinalRuleResult::Accept)),
             "R" => Ok(RuleResult::Finalize(FinalRuleResult::Reject)),
             name => Ok(RuleResult::SendToWorkflow(name.to_string())),
@@ -339,26 +347,20 @@ struct Part {
 }
 
 impl Part {
-    fn parse(input: &str) -> Result<Part, String> {
+    /// One part per line, like `{x=787,m=2655,a=1222,s=2876}`
+    fn parse(cursor: &mut Cursor) -> Result<Part, ParseError> {
         let mut ratings = [0i64; 4];
 
-        // trim off the curly braces
-        let input = &input[1..input.len() - 1];
-
-        let rating_strs = input.split(',').collect_vec();
-        if rating_strs.len() != 4 {
-            return Err(format!("invalid part (bad number of ratings): {}", input));
-        }
-
-        for (i, rating_str) in rating_strs.into_iter().enumerate() {
-            let chars = rating_str.chars().collect_vec();
-            if chars.len() < 3 {
-                return Err(format!("invalid part (rating too short): {}", input));
+        cursor.expect_literal("{")?;
+        for (i, (rating, category)) in ratings.iter_mut().zip(["x", "m", "a", "s"]).enumerate() {
+            if i > 0 {
+                cursor.expect_literal(",")?;
             }
-            ratings[i] = rating_str[2..]
-                .parse()
-                .map_err(|err| format!("invalid part (bad rating): {}: {}", input, err))?;
+            cursor.expect_literal(category)?;
+            cursor.expect_literal("=")?;
+            *rating = cursor.integer()?;
         }
+        cursor.expect_literal("}")?;
 
         Ok(Part { ratings })
     }
@@ -399,31 +401,20 @@ impl Part {
     }
 }
 
+/// Every combination of ratings within a 4-D box, one axis per category
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct RangedPart {
-    ranges: [SortedDisjointIntervalList<i64>; 4],
+    ratings: HyperRect<4>,
 }
 
 impl RangedPart {
     fn new_all() -> RangedPart {
-        let ranges = [
-            SortedDisjointIntervalList::new(vec![Interval::new(1..4001)]),
-            SortedDisjointIntervalList::new(vec![Interval::new(1..4001)]),
-            SortedDisjointIntervalList::new(vec![Interval::new(1..4001)]),
-            SortedDisjointIntervalList::new(vec![Interval::new(1..4001)]),
-        ];
-        RangedPart { ranges }
-    }
-
-    fn combination_counts(&self) -> u64 {
-        let mut combinations = 1;
-        for range in &self.ranges {
-            combinations *= range.span();
+        RangedPart {
+            ratings: HyperRect::from_inclusive_corners([*RATINGS.start(); 4], [*RATINGS.end(); 4]),
         }
-        combinations
     }
 
-    fn split_range(&self, condition: &Condition) -> (RangedPart, RangedPart) {
+    fn split_range(&self, condition: &Condition) -> (Vec<RangedPart>, Vec<RangedPart>) {
         let condition_index = match condition.category {
             PartCategory::ExtremelyCoolLooking => 0,
             PartCategory::Musical => 1,
@@ -431,36 +422,137 @@ impl RangedPart {
             PartCategory::Shiny => 3,
         };
 
-        let relevant_interval_list = &self.ranges[condition_index];
-
-        let passing_interval_list = match condition.ordering {
+        // each rule compares against a single value, so the box only needs cutting there
+        let value = condition.value;
+        let (passing, failing) = match condition.ordering {
+            cmp::Ordering::Less => {
+                let (below, above) = self.ratings.split_at(condition_index, value);
+                (vec![below], vec![above])
+            }
             cmp::Ordering::Greater => {
-                SortedDisjointIntervalList::new(vec![Interval::new(condition.value + 1..4001)])
+                let (below, above) = self
+                    .ratings
+                    .split_at(condition_index, value.saturating_add(1));
+                (vec![above], vec![below])
             }
-            cmp::Ordering::Less => {
-                SortedDisjointIntervalList::new(vec![Interval::new(1..condition.value)])
+            cmp::Ordering::Equal => {
+                let (below, rest) = self.ratings.split_at(condition_index, value);
+                let (equal, above) = rest.map_or((None, None), |rest| {
+                    rest.split_at(condition_index, value.saturating_add(1))
+                });
+                (vec![equal], vec![below, above])
             }
-            cmp::Ordering::Equal => SortedDisjointIntervalList::new(vec![Interval::new(
-                condition.value..condition.value + 1,
-            )]),
         };
 
-        let passing = relevant_interval_list.intersect(&passing_interval_list);
-        let failing = relevant_interval_list.subtract(&passing);
+        let into_parts = |rects: Vec<Option<HyperRect<4>>>| {
+            rects
+                .into_iter()
+                .flatten()
+                .map(|ratings| RangedPart { ratings })
+                .collect()
+        };
+        (into_parts(passing), into_parts(failing))
+    }
+}
 
-        let mut passing_ranges = self.ranges.clone();
-        passing_ranges[condition_index] = passing;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn test_example() {
+        let (workflows_section, parts_section) = split_sections(EXAMPLE).unwrap();
+        let workflows = Workflows::parse(&workflows_section).unwrap();
+        let parts = parts_section.lines(Part::parse).unwrap();
+
+        let accepted = workflows.accepted_from(&parts).unwrap();
+        assert_eq!(
+            accepted.iter().map(Part::sum_of_ratings).sum::<i64>(),
+            19114
+        );
+
+        let total_options = workflows
+            .sweep_accepted_ranges()
+            .unwrap()
+            .into_iter()
+            .map(|part_range| part_range.ratings)
+            .collect::<DisjointBoxes<4>>()
+            .volume()
+            .unwrap();
+        assert_eq!(total_options, 167409079868000);
+    }
 
-        let mut failing_ranges = self.ranges.clone();
-        failing_ranges[condition_index] = failing;
+    #[test]
+    fn test_parse_errors() {
+        let error = |workflows: &str| {
+            let input = format!("{}\n\n{{x=1,m=2,a=3,s=4}}", workflows);
+            let (workflows_section, _) = split_sections(&input).unwrap();
+            Workflows::parse(&workflows_section).unwrap_err()
+        };
+
+        assert_eq!(
+            error("in{s<1351:px,R}\npx{a<2006:A"),
+            "section 1: line 2 col 12: expected '}' found end of input"
+        );
+        assert_eq!(
+            error("in{s<1351:px,R}\npx{y<2006:A,R}"),
+            "section 1: line 2 col 4: invalid part category: y"
+        );
+        assert_eq!(
+            error("in{s=1351:px,R}"),
+            "section 1: line 1 col 5: expected '<' or '>'"
+        );
+        assert_eq!(
+            error("in{s<big:px,R}"),
+            "section 1: line 1 col 6: expected integer found 'b'"
+        );
+        assert_eq!(
+            error("in s<1351:px,R"),
+            "section 1: line 1 col 3: expected '{' found ' '"
+        );
+    }
+
+    #[test]
+    fn test_part_parse_errors() {
+        let error = |parts: &str| {
+            let input = format!("in{{A}}\n\n{}", parts);
+            let (_, parts_section) = split_sections(&input).unwrap();
+            parts_section.lines(Part::parse).unwrap_err()
+        };
 
-        (
-            RangedPart {
-                ranges: passing_ranges,
-            },
-            RangedPart {
-                ranges: failing_ranges,
-            },
-        )
+        assert_eq!(
+            error("x"),
+            "section 2: line 3 col 1: expected '{' found 'x'"
+        );
+        assert_eq!(
+            error("{"),
+            "section 2: line 3 col 2: expected 'x' found end of input"
+        );
+        assert_eq!(
+            error("{x=1,m=2,a=3,é=4}"),
+            "section 2: line 3 col 14: expected 's' found 'é'"
+        );
+        assert_eq!(
+            error("{x=1,m=2,a=3,s=4}\n{x=1,m=2}"),
+            "section 2: line 4 col 9: expected ',' found '}'"
+        );
     }
 }
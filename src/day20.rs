@@ -7,54 +7,60 @@ use std::{
 use itertools::Itertools;
 use num::Integer;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{
+    utils::{self, AdjacencyFormat, DiGraph, HitTimes},
+    AdventError, ExclusivePart, RunContext, Verbosity,
+};
 
 const INPUT_FILE: &str = "./resources/day20_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
     let press_count = 1000;
-    let print_out_pulses = false;
 
     let mut module_configuration = ModuleConfiguration::parse(&input)?;
 
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(module_configuration.to_dot());
+    }
+
     let mut low_pulse_count = 0;
     let mut high_pulse_count = 0;
     for i in 1..press_count + 1 {
         let pulses = module_configuration.press_button()?;
 
-        if print_out_pulses {
-            println!("button press #{}:", i);
-        }
+        ctx.trace(format_args!("button press #{}:", i));
         for pulse in pulses {
             match pulse.state {
                 PulseState::Low => low_pulse_count += 1,
                 PulseState::High => high_pulse_count += 1,
             }
-            if print_out_pulses {
-                println!("\t{}", pulse);
-            }
+            ctx.trace(format_args!("\t{}", pulse));
         }
     }
 
     Ok((low_pulse_count * high_pulse_count).to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
     let mut modules = ModuleConfiguration::parse(&input)?;
 
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(modules.to_dot());
+    }
+
     // ** NOTE: **
     //
     // This solution, unlike all the previous ones, is not generalizable or extensible.
@@ -65,89 +71,104 @@ fn part_two() -> Result<String, AdventError> {
     // This functions ONLY in the case of three very specific assumptions being true, which
     // were hand-picked based on MY specific input. These are the assumptions:
     //
-    // 1. The final destination module ("rx") has EXACTLY ONE input, named "df".
-    // 2. That penultimate module ("df") is a Conjunction module
-    // 3. The output frequencies of all the inputs to "df" can be found by iterating less that 5,000 times
+    // 1. The final destination module ("rx") has EXACTLY ONE input (named "df" in my input).
+    // 2. That penultimate module is a Conjunction module
+    // 3. Each input to the penultimate module sends a high pulse periodically, and the first two of those pulses
+    //    can be found by iterating less than 10,000 times
     //
     // This code exploits these assumptions to come to the correct solution in a reasonable time.
     //
     // Also of note: due to these assumptions, this part only works on the input, not an any
     // of the provided examples.
 
-    // get the inputs for the final module
-    let inputs_for_final = modules.inputs_for("df")?;
+    // get the inputs for the penultimate module, which is the only one feeding the final one
+    let penultimate = match modules.inputs_for("rx")?[..] {
+        [penultimate] => penultimate.clone(),
+        ref inputs => {
+            return Err(AdventError::Other(format!(
+                "expected exactly one input to rx, found {}",
+                inputs.len()
+            )))
+        }
+    };
+    let inputs_for_final = modules.inputs_for(&penultimate)?;
 
     // find the output frequencies for all the inputs to the final conjunction module
     let modules_to_analyze = inputs_for_final;
-    let output_frequencies = modules.output_frequencies_for(
+    let output_presses = modules.output_presses_for(
         modules_to_analyze
             .iter()
             .map(|s| s.to_string())
             .collect_vec()
             .as_slice(),
         PulseState::High,
-        5_000,
+        2,
+        10_000,
     )?;
 
-    // for (output, freq) in output_frequencies.iter() {
-    //     println!("{}: {:?}", output, freq);
-    // }
-
-    // make sure we actually got an output frequency from all of them within the limit
-    let validated_output_frequencies: Vec<(String, u64)> = output_frequencies
+    // make sure we actually saw every output twice within the limit, which gives us when
+    // each one first fires, and how often it fires after that
+    let output_hits: Vec<HitTimes> = output_presses
         .into_iter()
-        .map(|(k, v)| {
-            let validated_freq = v.ok_or(format!("output {} has no frequency", k))?;
-            Ok::<(String, u64), String>((k, validated_freq))
+        .map(|(k, presses)| match presses[..] {
+            [first, second] => HitTimes::periodic(first, second - first),
+            _ => Err(format!("output {} has no frequency", k)),
         })
         .try_collect()?;
 
-    // get the final frequency, which is the Lowest Common Multiple of all the output frequencies
-    let frequency_for_final = validated_output_frequencies
-        .into_iter()
-        .map(|(_, v)| v)
-        .reduce(|acc, freq| acc.lcm(&freq))
-        .unwrap();
+    // the final module fires once all of the outputs fire on the same press, which is only
+    // the lowest common multiple of their frequencies if they all started in step
+    let frequency_for_final = utils::first_common_time(&output_hits)?;
 
     Ok(frequency_for_final.to_string())
 }
 
 struct ModuleConfiguration {
     modules: HashMap<String, Box<dyn Module>>,
+    /// Which modules send pulses to which, including destinations which aren't modules at all
+    wiring: DiGraph<String, ()>,
 }
 
 impl ModuleConfiguration {
     fn parse(input: &str) -> Result<ModuleConfiguration, String> {
+        let lines = AdjacencyFormat::arrow()
+            .with_sigils(&['%', '&'])
+            .parse(input)?;
+
+        // wire up all the outputs for all modules
         let mut modules = HashMap::new();
-        for line in input.lines() {
-            let module: Box<dyn Module> = match line.chars().next().unwrap() {
-                'b' => Broadcaster::parse(line)?,
-                '%' => FlipFlop::parse(line)?,
-                '&' => Conjunction::parse(line)?,
-                _ => return Err(format!("invalid line: {}", line)),
+        let mut wiring = DiGraph::new();
+        for line in &lines {
+            let (name, outputs) = (line.name, line.neighbors.as_slice());
+            let module: Box<dyn Module> = match (line.sigil, name) {
+                (None, "broadcaster") => Box::new(Broadcaster::new(name, outputs)),
+                (Some('%'), _) => Box::new(FlipFlop::new(name, outputs)),
+                (Some('&'), _) => Box::new(Conjunction::new(name, outputs)),
+                _ => return Err(format!("line {}: unknown module {}", line.line, name)),
             };
-            modules.insert(module.name().to_string(), module);
-        }
-
-        // get all the outputs for all modules
-        let mut outputs = HashMap::new();
-        for module in modules.values() {
-            outputs.insert(module.name().to_string(), module.outputs().clone());
+            modules.insert(name.to_string(), module);
+            for (from, to, _) in line.edges() {
+                wiring.add_edge(from.to_string(), to.to_string(), ());
+            }
         }
 
         // we have to "hook up" the inputs of all the Conjunction modules
         for module in modules.values_mut() {
-            let name = module.name().to_string();
-            let mut inputs = Vec::new();
-            for (potential_input_name, potential_input_outputs) in outputs.iter() {
-                if potential_input_outputs.contains(&name) {
-                    inputs.push(potential_input_name.clone());
-                }
-            }
+            let index = wiring.add_node(module.name().to_string());
+            let inputs = wiring
+                .predecessors(index)
+                .unique()
+                .map(|input| wiring.node(input).clone())
+                .collect_vec();
             module.set_inputs(inputs.as_slice());
         }
 
-        Ok(ModuleConfiguration { modules })
+        Ok(ModuleConfiguration { modules, wiring })
+    }
+
+    /// The wiring between modules in Graphviz's dot format
+    fn to_dot(&self) -> String {
+        self.wiring.to_dot(|name| name.clone(), |_| None)
     }
 
     fn press_button(&mut self) -> Result<Vec<Pulse>, String> {
@@ -174,92 +195,82 @@ impl ModuleConfiguration {
         Ok(pulse_record)
     }
 
-    fn _output_frequencies_for_all(
+    fn _output_presses_for_all(
         &mut self,
         state: PulseState,
+        count: usize,
         limit: u64,
-    ) -> Result<HashMap<String, Option<u64>>, String> {
+    ) -> Result<HashMap<String, Vec<u64>>, String> {
         let all_modules = self.modules.keys().map(|x| x.clone()).collect_vec();
-        self.output_frequencies_for(all_modules.as_slice(), state, limit)
+        self.output_presses_for(all_modules.as_slice(), state, count, limit)
     }
 
-    fn output_frequencies_for(
+    /// The first `count` button presses on which each of `modules` sends a pulse of `state`
+    fn output_presses_for(
         &mut self,
         modules: &[String],
         state: PulseState,
+        count: usize,
         limit: u64,
-    ) -> Result<HashMap<String, Option<u64>>, String> {
-        let mut cache = HashMap::new();
+    ) -> Result<HashMap<String, Vec<u64>>, String> {
+        let mut presses = HashMap::new();
         for name in modules {
-            cache.insert(name.clone(), None);
+            presses.insert(name.clone(), Vec::new());
         }
 
-        self.output_frequencies(cache, state, limit)
-
-        // let module = self
-        //     .modules
-        //     .get(name)
-        //     .ok_or(format!("module not found: {}", name))?;
-
-        // Ok(module.pulse_frequency(state, &self.modules))
-    }
-
-    fn output_frequencies(
-        &mut self,
-        mut cache: HashMap<String, Option<u64>>,
-        state: PulseState,
-        limit: u64,
-    ) -> Result<HashMap<String, Option<u64>>, String> {
-        // let mut output_freqs = HashMap::new();
-        // output_freqs.insert("button".to_string(), None);
-        // for name in self.modules.keys() {
-        //     output_freqs.insert(name.clone(), None);
-        // }
-
         let mut press_count = 1;
         loop {
             let pulses = self.press_button()?;
             for pulse in pulses {
                 if pulse.state == state {
-                    if let Some(output_freq) = cache.get_mut(&pulse.from) {
-                        if output_freq.is_none() {
-                            *output_freq = Some(press_count);
+                    if let Some(output_presses) = presses.get_mut(&pulse.from) {
+                        if output_presses.len() < count
+                            && output_presses.last() != Some(&press_count)
+                        {
+                            output_presses.push(press_count);
                         }
                     }
                 }
             }
 
             press_count += 1;
-            if press_count > limit || cache.values().all(|x| x.is_some()) {
+            if press_count > limit || presses.values().all(|p| p.len() >= count) {
                 break;
             }
         }
-        Ok(cache)
+        Ok(presses)
     }
 
     fn inputs_for(&self, name: &str) -> Result<Vec<&String>, String> {
-        let module = self
-            .modules
-            .get(name)
+        let index = self
+            .wiring
+            .index_of(&name.to_string())
             .ok_or(format!("module not found: {}", name))?;
 
-        Ok(module.inputs())
+        Ok(self
+            .wiring
+            .predecessors(index)
+            .unique()
+            .map(|input| self.wiring.node(input))
+            .collect())
     }
 
-    fn _outputs_for(&self, name: &str) -> Result<&Vec<String>, String> {
-        let module = self
-            .modules
-            .get(name)
+    fn _outputs_for(&self, name: &str) -> Result<Vec<&String>, String> {
+        let index = self
+            .wiring
+            .index_of(&name.to_string())
             .ok_or(format!("module not found: {}", name))?;
 
-        Ok(module.outputs())
+        Ok(self
+            .wiring
+            .successors(index)
+            .map(|output| self.wiring.node(output))
+            .collect())
     }
 }
 
 trait Module {
     fn name(&self) -> &str;
-    fn inputs(&self) -> Vec<&String>;
-    fn outputs(&self) -> &Vec<String>;
     fn set_inputs(&mut self, inputs: &[String]);
     fn pulse_frequency(&self, state: PulseState, modules: &HashMap<String, Box<dyn Module>>)
         -> u64;
@@ -301,19 +312,12 @@ struct Broadcaster {
 }
 
 impl Broadcaster {
-    fn parse(line: &str) -> Result<Box<dyn Module>, String> {
-        let (declaration, outputs) = line
-            .split_once(" -> ")
-            .ok_or(format!("invalid line: {}", line))?;
-
-        let name = declaration.to_string();
-        let outputs = outputs.split(", ").map(|s| s.to_string()).collect();
-
-        Ok(Box::new(Broadcaster {
-            name,
+    fn new(name: &str, outputs: &[&str]) -> Self {
+        Broadcaster {
+            name: name.to_string(),
             inputs: Vec::new(),
-            outputs,
-        }))
+            outputs: outputs.iter().map(|s| s.to_string()).collect(),
+        }
     }
 
     fn pulse_input_frequency(
@@ -337,14 +341,6 @@ impl Module for Broadcaster {
         &self.name
     }
 
-    fn inputs(&self) -> Vec<&String> {
-        self.inputs.iter().collect_vec()
-    }
-
-    fn outputs(&self) -> &Vec<String> {
-        &self.outputs
-    }
-
     fn set_inputs(&mut self, inputs: &[String]) {
         self.inputs = inputs.to_vec();
     }
@@ -379,20 +375,13 @@ struct FlipFlop {
 }
 
 impl FlipFlop {
-    fn parse(line: &str) -> Result<Box<dyn Module>, String> {
-        let (declaration, outputs) = line
-            .split_once(" -> ")
-            .ok_or(format!("invalid line: {}", line))?;
-
-        let name = declaration[1..].to_string();
-        let outputs = outputs.split(", ").map(|s| s.to_string()).collect();
-
-        Ok(Box::new(FlipFlop {
-            name,
+    fn new(name: &str, outputs: &[&str]) -> Self {
+        FlipFlop {
+            name: name.to_string(),
             inputs: Vec::new(),
-            outputs,
+            outputs: outputs.iter().map(|s| s.to_string()).collect(),
             is_on: false,
-        }))
+        }
     }
 
     fn pulse_input_frequency(
@@ -416,14 +405,6 @@ impl Module for FlipFlop {
         &self.name
     }
 
-    fn inputs(&self) -> Vec<&String> {
-        self.inputs.iter().collect_vec()
-    }
-
-    fn outputs(&self) -> &Vec<String> {
-        &self.outputs
-    }
-
     fn set_inputs(&mut self, inputs: &[String]) {
         self.inputs = inputs.to_vec();
     }
@@ -473,19 +454,12 @@ struct Conjunction {
 }
 
 impl Conjunction {
-    fn parse(line: &str) -> Result<Box<dyn Module>, String> {
-        let (declaration, outputs) = line
-            .split_once(" -> ")
-            .ok_or(format!("invalid line: {}", line))?;
-
-        let name = declaration[1..].to_string();
-        let outputs = outputs.split(", ").map(|s| s.to_string()).collect();
-
-        Ok(Box::new(Conjunction {
-            name,
+    fn new(name: &str, outputs: &[&str]) -> Self {
+        Conjunction {
+            name: name.to_string(),
             memory: HashMap::new(),
-            outputs,
-        }))
+            outputs: outputs.iter().map(|s| s.to_string()).collect(),
+        }
     }
 }
 
@@ -494,14 +468,6 @@ impl Module for Conjunction {
         &self.name
     }
 
-    fn inputs(&self) -> Vec<&String> {
-        self.memory.keys().collect_vec()
-    }
-
-    fn outputs(&self) -> &Vec<String> {
-        &self.outputs
-    }
-
     fn set_inputs(&mut self, inputs: &[String]) {
         for input in inputs {
             self.memory.insert(input.clone(), PulseState::Low);
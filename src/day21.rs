@@ -4,17 +4,19 @@ use std::{
     fs,
 };
 
+use itertools::Itertools;
+
 use crate::{
-    utils::{CardinalDirection, Grid, GridPoint, Point2D, QuadraticEquation},
-    AdventError, ExclusivePart,
+    utils::{DistanceMap, Grid, GridPoint, Polynomial, TiledGrid},
+    AdventError, ExclusivePart, RunContext,
 };
 
 const INPUT_FILE: &str = "./resources/day21_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
@@ -36,8 +38,11 @@ fn part_one() -> Result<String, AdventError> {
 // Take 2: 639051580070841 (CORRECT)
 // Take 3: 639051580070841 (OPTIMIZED) [also, really more like take 20]
 
-fn part_two() -> Result<String, AdventError> {
-    part_two_stepping()
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
+    ctx.solve_with(&[
+        ("stepping", &|_| part_two_stepping()),
+        ("pathfinding", &|_| part_two_pathfinding()),
+    ])
 }
 
 fn part_two_stepping() -> Result<String, AdventError> {
@@ -48,10 +53,10 @@ fn part_two_stepping() -> Result<String, AdventError> {
     let steps_per_data_point = 2 * map.tiles.width() as u64;
     let pre_steps = map.tiles.width() as u64 / 2;
 
-    map.tile(4)?;
+    map.make_infinite();
 
     let mut total_steps = 0;
-    let mut data_points = [Point2D::default(); 3];
+    let mut data_points = Vec::new();
 
     map.step_bulk(pre_steps)?;
     total_steps += pre_steps;
@@ -61,19 +66,16 @@ fn part_two_stepping() -> Result<String, AdventError> {
             map.step_bulk(steps_per_data_point)?;
             total_steps += steps_per_data_point;
         }
-        let data_point_y = map.num_possible_locations() as f64;
-        let data_point = Point2D::new(total_steps as f64, data_point_y);
-        data_points[i as usize] = data_point;
+        data_points.push((total_steps as i64, map.num_possible_locations() as i64));
     }
 
-    let quadratic = QuadraticEquation::from_points(&data_points)?;
+    let quadratic = Polynomial::fit(&data_points)?;
 
-    let solution = quadratic.solve_for_y(26501365.0).round();
+    let solution = quadratic.evaluate_integer(26501365)?;
 
     Ok(solution.to_string())
 }
 
-#[allow(dead_code)]
 fn part_two_pathfinding() -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
@@ -81,23 +83,21 @@ fn part_two_pathfinding() -> Result<String, AdventError> {
     let mut map = Map::parse(&input)?;
 
     let width = map.tiles.width() as u64;
-    let x_points = (0..3).map(|i| (width / 2) + (i * width));
+    let x_points = (0..3).map(|i| (width / 2) + (i * width)).collect_vec();
 
-    map.tile(2)?;
+    map.make_infinite();
 
-    let mut data_points = [Point2D::default(); 3];
+    let y_points = map.num_possible_locations_pathfinding(&x_points)?;
 
-    let mut cache = Grid::new_empty(map.tiles.width(), map.tiles.height());
-    cache.set_top_left(map.tiles.top_left().clone());
-    for (i, x) in x_points.enumerate() {
-        // let y = map.num_possible_locations_pathfinding(x)?;
-        let y = map.num_possible_locations_pathfinding_with_cache(x, &mut cache)?;
-        data_points[i as usize] = Point2D::new(x as f64, y as f64);
-    }
+    let data_points = x_points
+        .into_iter()
+        .zip(y_points)
+        .map(|(x, y)| (x as i64, y as i64))
+        .collect_vec();
 
-    let quadratic = QuadraticEquation::from_points(&data_points)?;
+    let quadratic = Polynomial::fit(&data_points)?;
 
-    let solution = quadratic.solve_for_y(26501365.0).round();
+    let solution = quadratic.evaluate_integer(26501365)?;
 
     Ok(solution.to_string())
 }
@@ -107,6 +107,7 @@ struct Map {
     tiles: Grid<Tile>,
     possible_locations: HashSet<GridPoint>,
     steps_taken: u64,
+    infinite: bool,
 }
 
 impl Map {
@@ -131,119 +132,53 @@ impl Map {
             tiles,
             possible_locations,
             steps_taken: 0,
+            infinite: false,
         })
     }
 
-    fn tile(&mut self, times: u64) -> Result<(), String> {
-        let start_location = self
-            .tiles
-            .find(|tile| *tile == Tile::Start)
-            .map(|entry| entry.point)
-            .ok_or(format!("No start location"))?;
-
-        let mut horizontal = self.clone();
-        horizontal.tiles.set(&start_location, Tile::GardenPlot)?;
-        horizontal.possible_locations.clear();
-
-        for _ in 0..times {
-            // tile horizontally
-            self.tiles
-                .append_in_direction(&CardinalDirection::East, horizontal.tiles.clone())?;
-
-            self.tiles
-                .append_in_direction(&CardinalDirection::West, horizontal.tiles.clone())?;
-        }
-
-        let mut vertical = self.clone();
-        vertical.tiles.set(&start_location, Tile::GardenPlot)?;
-
-        for _ in 0..times {
-            // tile vertically
-            self.tiles
-                .append_in_direction(&CardinalDirection::North, vertical.tiles.clone())?;
-
-            self.tiles
-                .append_in_direction(&CardinalDirection::South, vertical.tiles.clone())?;
-        }
-        Ok(())
+    /// Treats the map as repeating infinitely in every direction
+    fn make_infinite(&mut self) {
+        self.infinite = true;
     }
 
-    #[allow(dead_code)]
-    fn num_possible_locations_pathfinding(&self, steps: u64) -> Result<u64, String> {
-        let start = self
-            .tiles
-            .find(|tile| *tile == Tile::Start)
-            .ok_or("No start location")?
-            .point;
-        let mut possibilities = 0;
-        for entry in self.tiles.entries_matching(|tile| *tile != Tile::Rocks) {
-            // check if we even need to both pathfinding
-            // since we definitely could only reach tiles
-            // that are AT MOST the manhattan distance steps away
-            if entry.point.manhattan_distance_to(&start) <= steps {
-                // it might be possible, so we have to try pathfinding
-                let true_distance =
-                    match self
-                        .tiles
-                        .astar_distance_othogonal(&entry.point, &start, |tile| *tile != Tile::Rocks)
-                    {
-                        Some(distance) => distance,
-                        None => {
-                            // no path? no way we could step here!
-                            continue;
-                        }
-                    };
-
-                if true_distance <= steps && true_distance % 2 == steps % 2 {
-                    possibilities += 1;
-                }
-            }
-        }
-        Ok(possibilities)
+    fn can_step_onto(&self, point: &GridPoint) -> bool {
+        let tile = if self.infinite {
+            TiledGrid::new(&self.tiles).get(point)
+        } else {
+            self.tiles.get(point)
+        };
+        tile.is_some_and(|tile| *tile != Tile::Rocks)
     }
 
-    fn num_possible_locations_pathfinding_with_cache(
-        &self,
-        steps: u64,
-        cache: &mut Grid<Option<u64>>,
-    ) -> Result<u64, String> {
+    /// Count the locations that could be reached in exactly each of the given numbers of steps
+    ///
+    /// This only takes a single search, out to the largest number of steps
+    fn num_possible_locations_pathfinding(&self, steps: &[u64]) -> Result<Vec<u64>, String> {
         let start = self
             .tiles
             .find(|tile| *tile == Tile::Start)
             .ok_or("No start location")?
             .point;
-        let mut possibilities = 0;
-        println!("pathfinding with cache: {} steps", steps);
-        for entry in self.tiles.entries_matching(|tile| *tile != Tile::Rocks) {
-            // check if we even need to both pathfinding
-            // since we definitely could only reach tiles
-            // that are AT MOST the manhattan distance steps away
-            let manhattan_distance = entry.point.manhattan_distance_to(&start);
-            if manhattan_distance <= steps {
-                println!(
-                    "\tdoing the hard work! from {} to {} (md: {})",
-                    entry.point, start, manhattan_distance
-                );
-                // it might be possible, so we have to try pathfinding
-                let true_distance = match self.tiles.astar_distance_othogonal_with_cache(
-                    &entry.point,
-                    &start,
-                    |tile| *tile != Tile::Rocks,
-                    cache,
-                ) {
-                    Some(distance) => distance,
-                    None => {
-                        // no path? no way we could step here!
-                        continue;
-                    }
-                };
-
-                if true_distance <= steps && true_distance % 2 == steps % 2 {
-                    possibilities += 1;
-                }
-            }
-        }
-        Ok(possibilities)
+        let max_steps = steps.iter().copied().max().unwrap_or(0);
+        let traversable = |tile: &Tile| *tile != Tile::Rocks;
+
+        let counts = if self.infinite {
+            let distances =
+                TiledGrid::new(&self.tiles).bfs_distances_within([start], traversable, max_steps);
+            steps
+                .iter()
+                .map(|&steps| distances.num_reachable_with_parity(steps) as u64)
+                .collect()
+        } else {
+            let distances = self
+                .tiles
+                .bfs_distances_within([start], traversable, max_steps);
+            steps
+                .iter()
+                .map(|&steps| distances.num_reachable_with_parity(steps) as u64)
+                .collect()
+        };
+        Ok(counts)
     }
 
     fn num_possible_locations(&self) -> usize {
@@ -261,10 +196,7 @@ impl Map {
         for step_count in 0..times {
             for location in frontier.drain() {
                 for neighbor in location.orthogonal_neighbors() {
-                    if self.tiles.is_within_bounds(&neighbor)
-                        && *self.tiles.must_get(&neighbor) != Tile::Rocks
-                        && !steppable.contains_key(&neighbor)
-                    {
+                    if self.can_step_onto(&neighbor) && !steppable.contains_key(&neighbor) {
                         next_frontier.insert(neighbor);
                     }
                 }
@@ -287,7 +219,30 @@ impl Map {
     }
 
     fn get_display_string(&self) -> Result<String, String> {
-        let mut display_grid = self.tiles.clone().map_all(|tile| match tile {
+        let mut tiles = if self.infinite {
+            // only show as much of the infinite map as we need to see every location
+            let top_left = self.tiles.top_left();
+            let (mut min_x, mut min_y) = (top_left.x, top_left.y);
+            let (mut max_x, mut max_y) = (
+                top_left.x + self.tiles.width() as i64 - 1,
+                top_left.y + self.tiles.height() as i64 - 1,
+            );
+            for location in self.possible_locations.iter() {
+                min_x = min_x.min(location.x);
+                min_y = min_y.min(location.y);
+                max_x = max_x.max(location.x);
+                max_y = max_y.max(location.y);
+            }
+            TiledGrid::new(&self.tiles).window(
+                &(min_x, min_y).into(),
+                (max_x - min_x + 1) as usize,
+                (max_y - min_y + 1) as usize,
+            )?
+        } else {
+            self.tiles.clone()
+        };
+
+        let mut display_grid = tiles.map_all(|tile| match tile {
             Tile::Start => 'S',
             Tile::GardenPlot => '.',
             Tile::Rocks => '#',
@@ -1,23 +1,26 @@
 use std::{
     cmp,
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, VecDeque},
     fmt, fs,
 };
 
 use itertools::Itertools;
 
-use crate::{AdventError, ExclusivePart};
+use crate::{
+    utils::{span, DiGraph, HyperRect},
+    AdventError, ExclusivePart, RunContext, Verbosity,
+};
 
 const INPUT_FILE: &str = "./resources/day22_input.txt";
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
@@ -25,12 +28,16 @@ fn part_one() -> Result<String, AdventError> {
 
     bricks.settle()?;
 
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(bricks.to_dot());
+    }
+
     let removable_bricks = bricks.find_removable()?;
 
     Ok(removable_bricks.len().to_string())
 }
 
-fn part_two() -> Result<String, AdventError> {
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
     // read input file
     let input = fs::read_to_string(INPUT_FILE)?;
 
@@ -38,6 +45,10 @@ fn part_two() -> Result<String, AdventError> {
 
     bricks.settle()?;
 
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(bricks.to_dot());
+    }
+
     let chain_reaction_counts = bricks.find_chain_reaction_counts()?;
 
     let mut total_chain_reaction_count = 0;
@@ -54,6 +65,8 @@ struct Bricks {
 
 impl Bricks {
     fn parse(input: &str) -> Result<Bricks, String> {
+        let _span = span!("parse");
+
         let mut bricks = Vec::new();
         for line in input.lines() {
             bricks.push(Brick::parse(line)?);
@@ -63,6 +76,8 @@ impl Bricks {
     }
 
     fn settle(&mut self) -> Result<(), String> {
+        let _span = span!("settle");
+
         // try to settle each bricks, which are all assumed to not be settled
         // we keep track of the indices of bricks that need to be settled instead of
         // the bricks themselves because we need to be able to change them at any point mutably
@@ -74,10 +89,10 @@ impl Bricks {
         let mut sorted_bricks = self.bricks.iter().enumerate().collect_vec();
         sorted_bricks.sort_by(|(_, a), (_, b)| {
             // sort ascending by z
-            match a.a.z().cmp(&b.a.z()) {
+            match a.bottom().cmp(&b.bottom()) {
                 cmp::Ordering::Less => cmp::Ordering::Less,
                 cmp::Ordering::Greater => cmp::Ordering::Greater,
-                cmp::Ordering::Equal => a.b.z().cmp(&b.b.z()),
+                cmp::Ordering::Equal => a.top().cmp(&b.top()),
             }
         });
 
@@ -127,20 +142,17 @@ impl Bricks {
             let brick = &self.bricks[index];
             // check if this brick is on the ground.
             // if so, it's settled, supported by no other bricks, and can't be dropped
-            if brick.a.z() == 1 || brick.b.z() == 1 {
+            if brick.bottom() == 1 {
                 return Ok(Vec::new());
             }
 
             // find bricks which are directly below this brick
-            let mut supporting_bricks = Vec::new();
-            let below_points = brick.points_below().collect_vec();
-            for other_brick in self.bricks.iter() {
-                for below_point in below_points.iter() {
-                    if other_brick.contains_point(below_point) {
-                        supporting_bricks.push(other_brick.clone());
-                    }
-                }
-            }
+            let supporting_bricks = self
+                .bricks
+                .iter()
+                .filter(|other_brick| other_brick.is_directly_supporting(brick))
+                .cloned()
+                .collect_vec();
 
             if supporting_bricks.is_empty() {
                 // drop brick one level
@@ -152,123 +164,102 @@ impl Bricks {
         }
     }
 
-    fn get_support_maps(&self) -> (HashMap<&Brick, Vec<&Brick>>, HashMap<&Brick, Vec<&Brick>>) {
-        let mut supporting = HashMap::new();
-        let mut supported_by = HashMap::new();
+    /// Which bricks rest directly on which, with an edge from each brick to the bricks it holds up
+    ///
+    /// The ground is a node too, holding up every brick on the bottom level
+    fn support_graph(&self) -> DiGraph<SupportNode<'_>, ()> {
+        let _span = span!("support graph");
+
+        let mut graph = DiGraph::new();
+        let ground = graph.add_node(SupportNode::Ground);
         for a in &self.bricks {
-            let mut supporting_a = Vec::new();
-            let mut supported_by_a = Vec::new();
+            let a_index = graph.add_node(SupportNode::Brick(a));
+            if a.bottom() == 1 {
+                graph.add_edge_between(ground, a_index, ());
+            }
             for b in &self.bricks {
-                if b.is_directly_supporting(a) {
-                    supporting_a.push(b);
-                } else if b.is_directly_supported_by(a) {
-                    supported_by_a.push(b);
+                if a.is_directly_supporting(b) {
+                    graph.add_edge(SupportNode::Brick(a), SupportNode::Brick(b), ());
                 }
             }
-            supporting.insert(a, supporting_a);
-            supported_by.insert(a, supported_by_a);
         }
 
-        (supporting, supported_by)
+        graph
     }
 
     fn find_chain_reaction_counts(&self) -> Result<HashMap<&Brick, usize>, String> {
-        // get support maps
-        let (supporting, supported_by) = self.get_support_maps();
+        let _span = span!("chain reactions");
+
+        let graph = self.support_graph();
+        let ground = graph
+            .index_of(&SupportNode::Ground)
+            .ok_or("support graph has no ground")?;
+
+        // removing a brick makes another one fall exactly when every way down to the ground from
+        // that other brick passes through it, which is to say when it dominates the other brick
+        let dominators = graph.dominators(ground);
 
         let mut chain_reaction_counts = HashMap::new();
         for a in &self.bricks {
-            let chain_reaction_count =
-                self.total_dependent_bricks_for(a, &supporting, &supported_by, &mut HashSet::new());
-            chain_reaction_counts.insert(a, chain_reaction_count);
-        }
-        Ok(chain_reaction_counts)
-    }
-
-    fn total_dependent_bricks_for<'a, 'b>(
-        &self,
-        a: &'b Brick,
-        supporting: &HashMap<&'a Brick, Vec<&'a Brick>>,
-        supported_by: &HashMap<&'a Brick, Vec<&'a Brick>>,
-        resolved: &mut HashSet<&'b Brick>,
-    ) -> usize
-    where
-        'a: 'b,
-    {
-        if resolved.contains(a) {
-            return 0;
+            chain_reaction_counts.insert(a, 0);
         }
-        resolved.insert(a);
-
-        let mut dependencies = 0;
-        let mut will_collapse = Vec::new();
-        for b in supported_by[a].iter().copied() {
-            // if a is the only brick supporting b, b will definitely collapse
-            if supporting[b].len() == 1 {
-                will_collapse.push(b);
-                continue;
-            }
-
-            // otherwise, b can only collapse if ALL of it supports are already marked as collapsing
-            let mut can_still_collapse = true;
-            for c in supporting[b].iter().copied() {
-                if !resolved.contains(c) {
-                    can_still_collapse = false;
+        for node in 0..graph.len() {
+            let mut dominator = dominators[node].ok_or("every brick should rest on something")?;
+            while dominator != ground {
+                if let SupportNode::Brick(brick) = graph.node(dominator) {
+                    *chain_reaction_counts.entry(*brick).or_default() += 1;
                 }
+                dominator = dominators[dominator].ok_or("every brick should rest on something")?;
             }
-
-            // if so, mark b as collapsing
-            if can_still_collapse {
-                will_collapse.push(b);
-            }
-        }
-
-        // for each depending brick, add its dependencies and itself to our count
-        for collapsing_brick in will_collapse {
-            dependencies += 1 + self.total_dependent_bricks_for(
-                collapsing_brick,
-                supporting,
-                supported_by,
-                resolved,
-            );
         }
-        dependencies
+        Ok(chain_reaction_counts)
     }
 
     fn find_removable(&self) -> Result<Vec<&Brick>, String> {
-        // get support maps
-        let (supporting, supported_by) = self.get_support_maps();
+        let _span = span!("removable bricks");
+
+        let graph = self.support_graph();
 
         let mut removable = Vec::new();
         for a in &self.bricks {
-            // if a brick is not supporting any other bricks, it is removable
-            if supported_by[a].is_empty() {
+            let a_index = graph
+                .index_of(&SupportNode::Brick(a))
+                .ok_or(format!("brick {} is missing from the support graph", a))?;
+
+            // a brick is removable ONLY IF all the bricks it supports (if any) are
+            // also supported by other bricks
+            if graph
+                .successors(a_index)
+                .all(|b| graph.predecessors(b).count() > 1)
+            {
                 removable.push(a);
-            } else {
-                // otherwise, a brick is removable ONLY IF all the bricks it supports are
-                // also supported by other bricks
-                let mut is_removable = true;
-                for b in supported_by[a].iter() {
-                    if supporting[b].len() == 1 {
-                        // only supported by a! this brick is not removable
-                        is_removable = false;
-                        break;
-                    }
-                }
-                if is_removable {
-                    removable.push(a);
-                }
             }
         }
 
         Ok(removable)
     }
+
+    /// Which settled bricks rest on which, in Graphviz's dot format
+    fn to_dot(&self) -> String {
+        self.support_graph().to_dot(
+            |node| match node {
+                SupportNode::Ground => "ground".to_string(),
+                SupportNode::Brick(brick) => brick.to_string(),
+            },
+            |_| None,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SupportNode<'a> {
+    Ground,
+    Brick(&'a Brick),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Brick {
-    a: BlockPoint,
-    b: BlockPoint,
+    shape: HyperRect<3>,
     is_settled: bool,
 }
 
@@ -278,156 +269,52 @@ impl Brick {
             .split_once('~')
             .ok_or(format!("invalid line: {}", line))?;
 
-        let p1_components: Vec<i64> = p1
-            .split(',')
-            .map(|s| {
-                s.parse::<i64>()
-                    .map_err(|err| format!("failed to parse {} as i64: {}", s, err))
-            })
-            .try_collect()?;
-
-        let p2_components: Vec<i64> = p2
-            .split(',')
-            .map(|s| {
-                s.parse::<i64>()
-                    .map_err(|err| format!("failed to parse {} as i64: {}", s, err))
-            })
-            .try_collect()?;
-
-        let p1: BlockPoint = p1_components.into();
-        let p2: BlockPoint = p2_components.into();
-
-        let min = p1.min_components(&p2);
-        let max = p2.max_components(&p2);
+        let parse_point = |point: &str| -> Result<[i64; 3], String> {
+            let components: Vec<i64> = point
+                .split(',')
+                .map(|s| {
+                    s.parse::<i64>()
+                        .map_err(|err| format!("failed to parse {} as i64: {}", s, err))
+                })
+                .try_collect()?;
+            components
+                .try_into()
+                .map_err(|_| format!("invalid point (expected 3 components): {}", point))
+        };
 
         Ok(Brick {
-            a: min,
-            b: max,
+            shape: HyperRect::from_inclusive_corners(parse_point(p1)?, parse_point(p2)?),
             is_settled: false,
         })
     }
 
-    fn drop(&mut self, levels: i64) {
-        self.a.0[2] -= levels;
-        self.b.0[2] -= levels;
+    fn bottom(&self) -> i64 {
+        self.shape.min()[2]
     }
 
-    fn points_above(&self) -> impl Iterator<Item = BlockPoint> + '_ {
-        self.encompassing_points().filter_map(|p| {
-            let above = p.above();
-            if !self.contains_point(&above) {
-                Some(above)
-            } else {
-                None
-            }
-        })
+    fn top(&self) -> i64 {
+        self.shape.max_inclusive()[2]
     }
 
-    fn points_below(&self) -> impl Iterator<Item = BlockPoint> + '_ {
-        self.encompassing_points().filter_map(|p| {
-            let below = p.below();
-            if !self.contains_point(&below) {
-                Some(below)
-            } else {
-                None
-            }
-        })
-    }
-
-    fn encompassing_points(&self) -> impl Iterator<Item = BlockPoint> {
-        let mut points = Vec::new();
-
-        // only one of these loops will have more than one iteration
-        // so the efficiency is not nearly as bad as it appears here
-        for x in self.a.x()..=self.b.x() {
-            for y in self.a.y()..=self.b.y() {
-                for z in self.a.z()..=self.b.z() {
-                    points.push(BlockPoint::new(x, y, z));
-                }
-            }
-        }
-        points.into_iter()
-    }
-
-    fn contains_point(&self, p: &BlockPoint) -> bool {
-        self.a.x() <= p.x()
-            && p.x() <= self.b.x()
-            && self.a.y() <= p.y()
-            && p.y() <= self.b.y()
-            && self.a.z() <= p.z()
-            && p.z() <= self.b.z()
+    fn drop(&mut self, levels: i64) {
+        self.shape = self.shape.translated([0, 0, -levels]);
     }
 
     fn is_directly_supporting(&self, other: &Brick) -> bool {
-        other
-            .encompassing_points()
-            .any(|p| self.points_above().contains(&p))
-    }
-
-    fn is_directly_supported_by(&self, other: &Brick) -> bool {
-        other.is_directly_supporting(self)
+        // bricks never overlap each other, so this also rules out a brick supporting itself
+        !self.shape.overlaps(&other.shape)
+            && self.shape.translated([0, 0, 1]).overlaps(&other.shape)
     }
 }
 
 impl fmt::Display for Brick {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}~{}", self.a, self.b)
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct BlockPoint([i64; 3]);
-
-impl BlockPoint {
-    fn new(x: i64, y: i64, z: i64) -> BlockPoint {
-        BlockPoint([x, y, z])
-    }
-
-    fn x(&self) -> i64 {
-        self.0[0]
-    }
-
-    fn y(&self) -> i64 {
-        self.0[1]
-    }
-
-    fn z(&self) -> i64 {
-        self.0[2]
-    }
-
-    fn min_components(&self, other: &BlockPoint) -> BlockPoint {
-        BlockPoint([
-            self.0[0].min(other.0[0]),
-            self.0[1].min(other.0[1]),
-            self.0[2].min(other.0[2]),
-        ])
-    }
-
-    fn max_components(&self, other: &BlockPoint) -> BlockPoint {
-        BlockPoint([
-            self.0[0].max(other.0[0]),
-            self.0[1].max(other.0[1]),
-            self.0[2].max(other.0[2]),
-        ])
-    }
-
-    fn above(&self) -> BlockPoint {
-        BlockPoint([self.0[0], self.0[1], self.0[2] + 1])
-    }
-
-    fn below(&self) -> BlockPoint {
-        BlockPoint([self.0[0], self.0[1], self.0[2] - 1])
-    }
-}
-
-impl From<Vec<i64>> for BlockPoint {
-    fn from(v: Vec<i64>) -> Self {
-        BlockPoint([v[0], v[1], v[2]])
-    }
-}
-
-impl fmt::Display for BlockPoint {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {}, {})", self.0[0], self.0[1], self.0[2])
+        let join = |point: &[i64; 3]| point.iter().join(",");
+        write!(
+            f,
+            "{}~{}",
+            join(self.shape.min()),
+            join(&self.shape.max_inclusive())
+        )
     }
 }
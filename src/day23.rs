@@ -1,16 +1,351 @@
-use crate::{AdventError, ExclusivePart};
+use std::fs;
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+use crate::{
+    utils::{CardinalDirection, DiGraph, Grid, GridPoint},
+    AdventError, ExclusivePart, RunContext, Verbosity,
+};
+
+const INPUT_FILE: &str = "./resources/day23_input.txt";
+
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
-        ExclusivePart::Two => part_two(),
+        ExclusivePart::One => part_one(ctx),
+        ExclusivePart::Two => part_two(ctx),
+    }
+}
+
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
+    // read input file
+    let input = fs::read_to_string(INPUT_FILE)?;
+
+    let map = HikingMap::parse(&input)?;
+
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(map.junction_graph_dot(true));
+    }
+
+    let longest_hike = map.longest_hike(true)?.ok_or("no hike leads to the end")?;
+
+    Ok(longest_hike.to_string())
+}
+
+fn part_two(ctx: &mut RunContext) -> Result<String, AdventError> {
+    // read input file
+    let input = fs::read_to_string(INPUT_FILE)?;
+
+    let map = HikingMap::parse(&input)?;
+
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(map.junction_graph_dot(false));
+    }
+
+    let longest_hike = map.longest_hike(false)?.ok_or("no hike leads to the end")?;
+
+    Ok(longest_hike.to_string())
+}
+
+struct HikingMap {
+    tiles: Grid<Tile>,
+    start: GridPoint,
+    end: GridPoint,
+}
+
+impl HikingMap {
+    fn parse(input: &str) -> Result<Self, String> {
+        let height = input.lines().count();
+        let width = input.lines().next().ok_or("empty map")?.len();
+
+        let mut tiles = Grid::new_empty(width, height);
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                tiles.set(&(x, y).into(), Tile::parse(c)?)?;
+            }
+        }
+
+        // the hike starts at the only path on the top row, and ends at the only one on the bottom
+        let only_path_in_row = |y: usize| -> Result<GridPoint, String> {
+            let paths = (0..width)
+                .map(|x| GridPoint::from((x, y)))
+                .filter(|point| tiles.get(point) == Some(&Tile::Path))
+                .collect::<Vec<_>>();
+            match paths[..] {
+                [point] => Ok(point),
+                _ => Err(format!("expected exactly one path on row {}", y)),
+            }
+        };
+        let start = only_path_in_row(0)?;
+        let end = only_path_in_row(height - 1)?;
+
+        Ok(Self { tiles, start, end })
+    }
+
+    /// Every point we can step to from `point`
+    ///
+    /// When the slopes are slippery, standing on one means the only way to go is downhill
+    fn moves(&self, point: &GridPoint, slippery: bool) -> Vec<GridPoint> {
+        let directions = match self.tiles.get(point) {
+            Some(Tile::Slope(direction)) if slippery => vec![*direction],
+            _ => CardinalDirection::all().to_vec(),
+        };
+        directions
+            .into_iter()
+            .map(|direction| point.neighbor_in_direction(direction))
+            .filter(|next| {
+                self.tiles
+                    .get(next)
+                    .is_some_and(|tile| *tile != Tile::Forest)
+            })
+            .collect()
+    }
+
+    /// The start, the end, and every point where the trail forks
+    fn junctions(&self) -> Vec<GridPoint> {
+        let mut junctions = vec![self.start, self.end];
+        junctions.extend(
+            self.tiles
+                .entries_matching(|tile| *tile != Tile::Forest)
+                .map(|entry| entry.point)
+                .filter(|point| self.moves(point, false).len() > 2),
+        );
+        junctions
+    }
+
+    /// The trails between junctions, with each single-width corridor squashed into one edge
+    /// weighted by its length
+    ///
+    /// A corridor only gets an edge in the directions it can be walked, so with slippery slopes
+    /// most of them are one-way, and corridors which lead nowhere are left out entirely
+    fn junction_graph(&self, slippery: bool) -> DiGraph<GridPoint, u64> {
+        let junctions = self.junctions();
+
+        let mut graph = DiGraph::new();
+        for junction in &junctions {
+            graph.add_node(*junction);
+        }
+
+        for junction in &junctions {
+            for first_step in self.moves(junction, slippery) {
+                let mut previous = *junction;
+                let mut current = first_step;
+                let mut length = 1;
+                while !junctions.contains(&current) {
+                    // corridors are a single tile wide, so there's at most one way onwards
+                    let Some(next) = self
+                        .moves(&current, slippery)
+                        .into_iter()
+                        .find(|next| *next != previous)
+                    else {
+                        break;
+                    };
+                    previous = current;
+                    current = next;
+                    length += 1;
+                }
+
+                if junctions.contains(&current) && current != *junction {
+                    graph.add_edge(*junction, current, length);
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// The junction graph in Graphviz's dot format, for looking at the shape of the maze
+    fn junction_graph_dot(&self, slippery: bool) -> String {
+        self.junction_graph(slippery)
+            .to_dot(|point| point.to_string(), |length| Some(length.to_string()))
+    }
+
+    /// The number of steps in the longest hike which never steps on the same tile twice,
+    /// or `None` if there's no way to the end at all
+    fn longest_hike(&self, slippery: bool) -> Result<Option<u64>, String> {
+        let graph = self.junction_graph(slippery);
+        if graph.len() > u64::BITS as usize {
+            return Err(format!(
+                "{} junctions are too many to track in a u64",
+                graph.len()
+            ));
+        }
+
+        let index_of = |point| graph.index_of(point).ok_or("junction missing from graph");
+        let start = index_of(&self.start)?;
+        let end = index_of(&self.end)?;
+
+        // the end is a dead end, so once we reach the only junction next to it, going anywhere
+        // else would cut us off from it for good
+        let mut neighbors_of_end = graph.predecessors(end).collect::<Vec<_>>();
+        neighbors_of_end.sort_unstable();
+        neighbors_of_end.dedup();
+        let last_junction = match neighbors_of_end[..] {
+            [last_junction] => Some(last_junction),
+            _ => None,
+        };
+
+        Ok(LongestPathSearch {
+            graph: &graph,
+            end,
+            last_junction,
+        }
+        .longest_from(start, 1 << start))
+    }
+}
+
+/// A depth-first search for the longest simple path, tracking visited junctions as bits
+struct LongestPathSearch<'a> {
+    graph: &'a DiGraph<GridPoint, u64>,
+    end: usize,
+    last_junction: Option<usize>,
+}
+
+impl LongestPathSearch<'_> {
+    fn longest_from(&self, node: usize, visited: u64) -> Option<u64> {
+        if node == self.end {
+            return Some(0);
+        }
+
+        let mut longest = None;
+        for edge in self.graph.out_edges(node) {
+            if visited & (1 << edge.to) != 0 {
+                continue;
+            }
+            if Some(node) == self.last_junction && edge.to != self.end {
+                continue;
+            }
+            if let Some(rest) = self.longest_from(edge.to, visited | (1 << edge.to)) {
+                longest = longest.max(Some(edge.label + rest));
+            }
+        }
+        longest
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
-    Err(AdventError::Unimplemented)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Path,
+    #[default]
+    Forest,
+    Slope(CardinalDirection),
+}
+
+impl Tile {
+    fn parse(c: char) -> Result<Self, String> {
+        match c {
+            '.' => Ok(Tile::Path),
+            '#' => Ok(Tile::Forest),
+            '^' => Ok(Tile::Slope(CardinalDirection::North)),
+            'v' => Ok(Tile::Slope(CardinalDirection::South)),
+            '>' => Ok(Tile::Slope(CardinalDirection::East)),
+            '<' => Ok(Tile::Slope(CardinalDirection::West)),
+            _ => Err(format!("invalid tile: {}", c)),
+        }
+    }
 }
 
-fn part_two() -> Result<String, AdventError> {
-    Err(AdventError::Unimplemented)
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// The example from the puzzle description
+    const PUZZLE_EXAMPLE: &str = "\
+#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+
+    const EXAMPLE: &str = "\
+#.#########
+#.......#.#
+#.#####.#.#
+#.#...#>..#
+#.#.#.#v#.#
+#...#...#.#
+###v#####.#
+#...>.....#
+#.#######v#
+#.........#
+#########.#";
+
+    /// Walk every simple path tile by tile, without any corridor compression
+    fn brute_force(map: &HikingMap, slippery: bool) -> Option<u64> {
+        fn walk(
+            map: &HikingMap,
+            slippery: bool,
+            point: GridPoint,
+            visited: &mut HashSet<GridPoint>,
+        ) -> Option<u64> {
+            if point == map.end {
+                return Some(0);
+            }
+            let mut longest = None;
+            for next in map.moves(&point, slippery) {
+                if visited.insert(next) {
+                    if let Some(rest) = walk(map, slippery, next, visited) {
+                        longest = longest.max(Some(rest + 1));
+                    }
+                    visited.remove(&next);
+                }
+            }
+            longest
+        }
+
+        walk(map, slippery, map.start, &mut HashSet::from([map.start]))
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        let map = HikingMap::parse(PUZZLE_EXAMPLE).unwrap();
+        assert_eq!(map.longest_hike(true).unwrap(), Some(94));
+        assert_eq!(map.longest_hike(false).unwrap(), Some(154));
+    }
+
+    #[test]
+    fn test_junction_graph_dot() {
+        let map = HikingMap::parse(PUZZLE_EXAMPLE).unwrap();
+        let graph = map.junction_graph(true);
+        let dot = map.junction_graph_dot(true);
+
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains(&format!("[label=\"{}\"]", map.start)));
+        let edges = (0..graph.len())
+            .map(|node| graph.out_edges(node).count())
+            .sum::<usize>();
+        assert_eq!(dot.matches(" -> ").count(), edges);
+    }
+
+    #[test]
+    fn test_longest_hike_matches_brute_force() {
+        let map = HikingMap::parse(EXAMPLE).unwrap();
+
+        for slippery in [true, false] {
+            assert_eq!(
+                map.longest_hike(slippery).unwrap(),
+                brute_force(&map, slippery),
+                "slippery: {}",
+                slippery
+            );
+        }
+        assert!(map.longest_hike(false).unwrap() > map.longest_hike(true).unwrap());
+    }
 }
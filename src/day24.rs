@@ -1,6 +1,19 @@
-use crate::{AdventError, ExclusivePart};
+use std::{fs, ops::RangeInclusive};
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+use itertools::Itertools;
+use num::{BigInt, BigRational, Signed, ToPrimitive};
+
+use crate::{
+    utils::{self, PathIntersection, Ray},
+    AdventError, ExclusivePart, RunContext,
+};
+
+const INPUT_FILE: &str = "./resources/day24_input.txt";
+
+/// The x and y values where crossing paths count for part one
+const TEST_AREA: RangeInclusive<i64> = 200_000_000_000_000..=400_000_000_000_000;
+
+pub fn run(epart: ExclusivePart, _ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
         ExclusivePart::One => part_one(),
         ExclusivePart::Two => part_two(),
@@ -8,9 +21,210 @@ pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
 }
 
 fn part_one() -> Result<String, AdventError> {
-    Err(AdventError::Unimplemented)
+    // read input file
+    let input = fs::read_to_string(INPUT_FILE)?;
+
+    let hailstones = Hailstones::parse(&input)?;
+
+    let crossings = hailstones.count_future_crossings_within(&TEST_AREA)?;
+
+    Ok(crossings.to_string())
 }
 
 fn part_two() -> Result<String, AdventError> {
-    Err(AdventError::Unimplemented)
+    // read input file
+    let input = fs::read_to_string(INPUT_FILE)?;
+
+    let hailstones = Hailstones::parse(&input)?;
+
+    let rock = hailstones.find_rock_throw()?;
+
+    Ok(rock.origin().iter().sum::<i64>().to_string())
+}
+
+struct Hailstones {
+    hailstones: Vec<Ray<3>>,
+}
+
+impl Hailstones {
+    fn parse(input: &str) -> Result<Self, String> {
+        let parse_vector = |vector: &str| -> Result<[i64; 3], String> {
+            let components: Vec<i64> = vector
+                .split(',')
+                .map(|s| {
+                    s.trim()
+                        .parse::<i64>()
+                        .map_err(|err| format!("failed to parse {} as i64: {}", s, err))
+                })
+                .try_collect()?;
+            components
+                .try_into()
+                .map_err(|_| format!("invalid vector (expected 3 components): {}", vector))
+        };
+
+        let hailstones = input
+            .lines()
+            .map(|line| {
+                let (position, velocity) = line
+                    .split_once('@')
+                    .ok_or(format!("invalid line: {}", line))?;
+                Ok(Ray::new(parse_vector(position)?, parse_vector(velocity)?))
+            })
+            .try_collect::<_, _, String>()?;
+
+        Ok(Self { hailstones })
+    }
+
+    /// The number of pairs of hailstones whose paths cross inside `area` (on both x and y),
+    /// looking only forwards in time and ignoring the z axis
+    fn count_future_crossings_within(&self, area: &RangeInclusive<i64>) -> Result<usize, String> {
+        let low = BigRational::from_integer(BigInt::from(*area.start()));
+        let high = BigRational::from_integer(BigInt::from(*area.end()));
+
+        let mut crossings = 0;
+        for (a, b) in self.hailstones.iter().map(flatten).tuple_combinations() {
+            match a.path_intersection(&b) {
+                PathIntersection::Point { time_a, time_b } => {
+                    let in_future = !time_a.is_negative() && !time_b.is_negative();
+                    let in_area = a
+                        .position_at(&time_a)
+                        .iter()
+                        .all(|value| low <= *value && *value <= high);
+                    if in_future && in_area {
+                        crossings += 1;
+                    }
+                }
+                PathIntersection::Parallel => {}
+                PathIntersection::Collinear => {
+                    return Err(format!("hailstones {} and {} share a path", a, b));
+                }
+            }
+        }
+
+        Ok(crossings)
+    }
+
+    /// The rock which, thrown from the right place at the right velocity, hits every hailstone
+    fn find_rock_throw(&self) -> Result<Ray<3>, String> {
+        // a rock at p with velocity v hits hailstone i exactly when (p - p_i) x (v - v_i) = 0.
+        // expanding that, p x v is the same for every hailstone, so subtracting the equations of
+        // two hailstones i and j leaves something linear:
+        //
+        //   p x (v_j - v_i) + (p_j - p_i) x v = p_j x v_j - p_i x v_i
+        //
+        // which is three equations, so two pairs of hailstones pin down all six unknowns, as
+        // long as the hailstones we pick aren't too alike
+        let first = self.hailstones.first().ok_or("no hailstones to hit")?;
+        let solution = self.hailstones[1..]
+            .iter()
+            .tuple_combinations()
+            .find_map(|(second, third)| {
+                let (mut matrix, mut rhs) = rock_equations(first, second);
+                let (more_matrix, more_rhs) = rock_equations(first, third);
+                matrix.extend(more_matrix);
+                rhs.extend(more_rhs);
+                utils::solve_linear_system(matrix, rhs).ok()
+            })
+            .ok_or("couldn't find hailstones which pin down the rock's throw")?;
+
+        let components = solution
+            .iter()
+            .map(|value| {
+                value
+                    .is_integer()
+                    .then(|| value.to_integer().to_i64())
+                    .flatten()
+                    .ok_or(format!("rock throw has a non-integer component {}", value))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rock = Ray::new(
+            [components[0], components[1], components[2]],
+            [components[3], components[4], components[5]],
+        );
+
+        // make sure the rock really does hit every hailstone, and only ever going forwards in time
+        for hailstone in &self.hailstones {
+            match rock.collision_time(hailstone) {
+                Some(time) if !time.is_negative() => {}
+                _ => return Err(format!("rock {} never hits hailstone {}", rock, hailstone)),
+            }
+        }
+
+        Ok(rock)
+    }
+}
+
+/// The x and y parts of a hailstone's path
+fn flatten(hailstone: &Ray<3>) -> Ray<2> {
+    let [x, y, _] = *hailstone.origin();
+    let [vx, vy, _] = *hailstone.velocity();
+    Ray::new([x, y], [vx, vy])
+}
+
+/// The three linear equations in `[p, v]` which a rock hitting both `a` and `b` has to satisfy
+fn rock_equations(a: &Ray<3>, b: &Ray<3>) -> (Vec<Vec<BigRational>>, Vec<BigRational>) {
+    let wide = |vector: &[i64; 3]| vector.map(i128::from);
+    let (pa, va) = (wide(a.origin()), wide(a.velocity()));
+    let (pb, vb) = (wide(b.origin()), wide(b.velocity()));
+
+    // p x w, where w = v_b - v_a, and u x v, where u = p_b - p_a, both written as matrices
+    let w = [vb[0] - va[0], vb[1] - va[1], vb[2] - va[2]];
+    let u = [pb[0] - pa[0], pb[1] - pa[1], pb[2] - pa[2]];
+    let p_coefficients = [[0, w[2], -w[1]], [-w[2], 0, w[0]], [w[1], -w[0], 0]];
+    let v_coefficients = [[0, -u[2], u[1]], [u[2], 0, -u[0]], [-u[1], u[0], 0]];
+
+    let (cross_a, cross_b) = (cross(pa, va), cross(pb, vb));
+
+    let rational = |value: i128| BigRational::from_integer(BigInt::from(value));
+    let matrix = (0..3)
+        .map(|row| {
+            p_coefficients[row]
+                .iter()
+                .chain(v_coefficients[row].iter())
+                .map(|&value| rational(value))
+                .collect()
+        })
+        .collect();
+    let rhs = (0..3)
+        .map(|row| rational(cross_b[row] - cross_a[row]))
+        .collect();
+
+    (matrix, rhs)
+}
+
+fn cross(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+
+    #[test]
+    fn test_example_crossings() {
+        let hailstones = Hailstones::parse(EXAMPLE).unwrap();
+
+        assert_eq!(hailstones.count_future_crossings_within(&(7..=27)), Ok(2));
+    }
+
+    #[test]
+    fn test_example_rock_throw() {
+        let hailstones = Hailstones::parse(EXAMPLE).unwrap();
+
+        assert_eq!(
+            hailstones.find_rock_throw(),
+            Ok(Ray::new([24, 13, 10], [-3, 1, 2]))
+        );
+    }
 }
@@ -1,16 +1,104 @@
-use crate::{AdventError, ExclusivePart};
+use std::fs;
 
-pub fn run(epart: ExclusivePart) -> Result<String, AdventError> {
+use crate::{
+    utils::{AdjacencyFormat, DiGraph},
+    AdventError, ExclusivePart, RunContext, Verbosity,
+};
+
+const INPUT_FILE: &str = "./resources/day25_input.txt";
+
+/// The number of wires that need to be disconnected to split the components in two
+const WIRES_TO_CUT: u64 = 3;
+
+pub fn run(epart: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match epart {
-        ExclusivePart::One => part_one(),
+        ExclusivePart::One => part_one(ctx),
         ExclusivePart::Two => part_two(),
     }
 }
 
-fn part_one() -> Result<String, AdventError> {
-    Err(AdventError::Unimplemented)
+fn part_one(ctx: &mut RunContext) -> Result<String, AdventError> {
+    // read input file
+    let input = fs::read_to_string(INPUT_FILE)?;
+
+    let wiring = Wiring::parse(&input)?;
+
+    if ctx.is_enabled(Verbosity::Trace) {
+        ctx.trace(wiring.to_dot());
+    }
+
+    let (a, b) = wiring.split_group_sizes()?;
+
+    Ok((a * b).to_string())
 }
 
 fn part_two() -> Result<String, AdventError> {
     Err(AdventError::Unimplemented)
 }
+
+struct Wiring {
+    /// Each wire is stored once, in the direction it was listed, but it connects both ways
+    components: DiGraph<String, ()>,
+}
+
+impl Wiring {
+    fn parse(input: &str) -> Result<Self, String> {
+        let components = AdjacencyFormat::colon()
+            .parse(input)?
+            .iter()
+            .flat_map(|line| line.edges())
+            .map(|(from, to, _)| (from.to_string(), to.to_string(), ()))
+            .collect();
+
+        Ok(Self { components })
+    }
+
+    /// The sizes of the two groups left after cutting the fewest possible wires
+    fn split_group_sizes(&self) -> Result<(usize, usize), String> {
+        let cut = self
+            .components
+            .min_cut(|_| 1)
+            .ok_or("not enough components to split in two")?;
+        if cut.weight != WIRES_TO_CUT {
+            return Err(format!(
+                "expected to cut {} wires, but the smallest cut is {}",
+                WIRES_TO_CUT, cut.weight
+            ));
+        }
+
+        Ok((cut.side.len(), self.components.len() - cut.side.len()))
+    }
+
+    /// The wiring in Graphviz's dot format, with each wire drawn in the direction it was listed
+    fn to_dot(&self) -> String {
+        self.components.to_dot(|name| name.clone(), |_| None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    #[test]
+    fn test_example_split() {
+        let wiring = Wiring::parse(EXAMPLE).unwrap();
+
+        let (a, b) = wiring.split_group_sizes().unwrap();
+        assert_eq!(a * b, 54);
+    }
+}
@@ -1,4 +1,7 @@
-use std::fmt::Display;
+use std::{
+    fmt::{Debug, Display},
+    io::{self, Write},
+};
 
 mod day01;
 mod day02;
@@ -48,6 +51,153 @@ impl Display for ExclusivePart {
     }
 }
 
+/// How much a solver should say about how it got its answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Just the answer
+    #[default]
+    Quiet,
+    /// The important steps along the way, with `-v`
+    Debug,
+    /// Everything, down to intermediate grids and search steps, with `-vv`
+    Trace,
+}
+
+/// Which way to the answer a solver should take, when it knows more than one
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum StrategyChoice {
+    /// Whichever strategy the solver lists first
+    #[default]
+    Default,
+    /// The strategy with this name, from `--strategy <name>`
+    Named(String),
+    /// Every strategy, failing unless they all agree, from `--cross-check`
+    CrossCheck,
+}
+
+/// One of the ways a solver can find its answer, with the name `--strategy` picks it by
+pub type Strategy<'a, T, E> = (&'a str, &'a dyn Fn(&mut RunContext) -> Result<T, E>);
+
+/// Everything a solver is handed by whoever runs it, apart from its input
+pub struct RunContext {
+    verbosity: Verbosity,
+    out: Box<dyn Write>,
+    strategy: StrategyChoice,
+    /// Whether the solver has offered any strategies to choose between
+    offered_strategies: bool,
+}
+
+impl RunContext {
+    pub fn new(verbosity: Verbosity, out: Box<dyn Write>) -> Self {
+        Self {
+            verbosity,
+            out,
+            strategy: StrategyChoice::Default,
+            offered_strategies: false,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: StrategyChoice) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// A context which never writes anything
+    pub fn quiet() -> Self {
+        Self::new(Verbosity::Quiet, Box::new(io::sink()))
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    /// Whether anything logged at `level` gets written, so expensive output can be skipped
+    /// entirely when it won't be
+    pub fn is_enabled(&self, level: Verbosity) -> bool {
+        level != Verbosity::Quiet && level <= self.verbosity
+    }
+
+    /// Write `message` on its own line, if the verbosity is at least `level`
+    pub fn log(&mut self, level: Verbosity, message: impl Display) {
+        if self.is_enabled(level) {
+            // this is only ever diagnostics, so failing to write them shouldn't fail the solver
+            let _ = writeln!(self.out, "{}", message);
+        }
+    }
+
+    pub fn debug(&mut self, message: impl Display) {
+        self.log(Verbosity::Debug, message);
+    }
+
+    pub fn trace(&mut self, message: impl Display) {
+        self.log(Verbosity::Trace, message);
+    }
+
+    /// Whether the solver called `solve_with`, so a `--strategy` it was given meant anything
+    pub fn offered_strategies(&self) -> bool {
+        self.offered_strategies
+    }
+
+    /// Find the answer with whichever of `strategies` was asked for, the first one by default
+    ///
+    /// When cross-checking, this runs every strategy and gives an error unless they all agree
+    pub fn solve_with<T, E>(&mut self, strategies: &[Strategy<'_, T, E>]) -> Result<T, E>
+    where
+        T: PartialEq + Debug,
+        E: From<String>,
+    {
+        self.offered_strategies = true;
+        let names = || strategies.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+
+        match self.strategy.clone() {
+            StrategyChoice::Default => {
+                let (_, solve) = strategies
+                    .first()
+                    .ok_or("no strategies to solve with".to_string())?;
+                solve(self)
+            }
+            StrategyChoice::Named(wanted) => {
+                let (_, solve) =
+                    strategies
+                        .iter()
+                        .find(|(name, _)| *name == wanted)
+                        .ok_or(format!(
+                            "unknown strategy {}, expected one of: {}",
+                            wanted,
+                            names().join(", ")
+                        ))?;
+                solve(self)
+            }
+            StrategyChoice::CrossCheck => {
+                let mut answers = Vec::new();
+                for (name, solve) in strategies {
+                    let answer = solve(self)?;
+                    self.debug(format_args!("strategy {} found {:?}", name, answer));
+                    answers.push((name, answer));
+                }
+
+                let (_, first) = answers
+                    .first()
+                    .ok_or("no strategies to solve with".to_string())?;
+                if answers.iter().any(|(_, answer)| answer != first) {
+                    let found = answers
+                        .iter()
+                        .map(|(name, answer)| format!("{} found {:?}", name, answer))
+                        .collect::<Vec<_>>();
+                    return Err(format!("strategies disagree: {}", found.join(", ")).into());
+                }
+                Ok(answers.swap_remove(0).1)
+            }
+        }
+    }
+}
+
+impl Default for RunContext {
+    fn default() -> Self {
+        Self::quiet()
+    }
+}
+
 pub enum AdventError {
     Unimplemented,
     Other(String),
@@ -71,33 +221,73 @@ impl From<std::io::Error> for AdventError {
     }
 }
 
-pub fn run(day: u32, part: ExclusivePart) -> Result<String, AdventError> {
+impl From<utils::ParseError> for AdventError {
+    fn from(value: utils::ParseError) -> Self {
+        Self::Other(value.to_string())
+    }
+}
+
+pub fn run(day: u32, part: ExclusivePart, ctx: &mut RunContext) -> Result<String, AdventError> {
     match day {
-        1 => day01::run(part),
-        2 => day02::run(part),
-        3 => day03::run(part),
-        4 => day04::run(part),
-        5 => day05::run(part),
-        6 => day06::run(part),
-        7 => day07::run(part),
-        8 => day08::run(part),
-        9 => day09::run(part),
-        10 => day10::run(part),
-        11 => day11::run(part),
-        12 => day12::run(part),
-        13 => day13::run(part),
-        14 => day14::run(part),
-        15 => day15::run(part),
-        16 => day16::run(part),
-        17 => day17::run(part),
-        18 => day18::run(part),
-        19 => day19::run(part),
-        20 => day20::run(part),
-        21 => day21::run(part),
-        22 => day22::run(part),
-        23 => day23::run(part),
-        24 => day24::run(part),
-        25 => day25::run(part),
+        1 => day01::run(part, ctx),
+        2 => day02::run(part, ctx),
+        3 => day03::run(part, ctx),
+        4 => day04::run(part, ctx),
+        5 => day05::run(part, ctx),
+        6 => day06::run(part, ctx),
+        7 => day07::run(part, ctx),
+        8 => day08::run(part, ctx),
+        9 => day09::run(part, ctx),
+        10 => day10::run(part, ctx),
+        11 => day11::run(part, ctx),
+        12 => day12::run(part, ctx),
+        13 => day13::run(part, ctx),
+        14 => day14::run(part, ctx),
+        15 => day15::run(part, ctx),
+        16 => day16::run(part, ctx),
+        17 => day17::run(part, ctx),
+        18 => day18::run(part, ctx),
+        19 => day19::run(part, ctx),
+        20 => day20::run(part, ctx),
+        21 => day21::run(part, ctx),
+        22 => day22::run(part, ctx),
+        23 => day23::run(part, ctx),
+        24 => day24::run(part, ctx),
+        25 => day25::run(part, ctx),
         _ => panic!("Day {} not implemented", day),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_with_strategies() {
+        let right = |_: &mut RunContext| Ok::<_, String>(2);
+        let wrong = |_: &mut RunContext| Ok::<_, String>(3);
+
+        let mut ctx = RunContext::quiet();
+        assert_eq!(
+            ctx.solve_with(&[("right", &right), ("wrong", &wrong)]),
+            Ok(2)
+        );
+        assert!(ctx.offered_strategies());
+
+        let mut ctx = RunContext::quiet().with_strategy(StrategyChoice::Named("wrong".into()));
+        assert_eq!(
+            ctx.solve_with(&[("right", &right), ("wrong", &wrong)]),
+            Ok(3)
+        );
+        assert!(ctx.solve_with(&[("right", &right)]).is_err());
+
+        let mut ctx = RunContext::quiet().with_strategy(StrategyChoice::CrossCheck);
+        assert_eq!(
+            ctx.solve_with(&[("right", &right), ("also", &right)]),
+            Ok(2)
+        );
+        assert!(ctx
+            .solve_with(&[("right", &right), ("wrong", &wrong)])
+            .is_err());
+    }
+}
@@ -1,6 +1,8 @@
-use std::{env, time::Instant};
+use std::{env, io, time::Instant};
 
-use advent_of_code_2023::{run, utils, AdventError, ExclusivePart, Parts};
+use advent_of_code_2023::{
+    run, utils, AdventError, ExclusivePart, Parts, RunContext, StrategyChoice, Verbosity,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Days {
@@ -8,23 +10,33 @@ pub enum Days {
     All,
 }
 
-fn main() -> Result<(), String> {
-    let args: Vec<String> = env::args().collect();
+/// Settings from the `-` flags on the command line, which can go anywhere among the arguments
+#[derive(Debug, Clone, Default)]
+struct Options {
+    /// Print the `span!` timings after each part
+    profile: bool,
+    /// How much the solvers write to stderr about what they're doing
+    verbosity: Verbosity,
+    /// Which strategy solvers with more than one should use
+    strategy: StrategyChoice,
+}
 
+fn main() -> Result<(), String> {
+    let (options, args) = get_options(env::args().skip(1))?;
     let days = get_days(args)?;
 
     match days {
         Days::Single(day, parts) => match parts {
-            Parts::Single(part) => run_and_print_day(day, part, false),
+            Parts::Single(part) => run_and_print_day(day, part, false, &options),
             Parts::Both => {
-                run_and_print_day(day, ExclusivePart::One, false);
-                run_and_print_day(day, ExclusivePart::Two, false);
+                run_and_print_day(day, ExclusivePart::One, false, &options);
+                run_and_print_day(day, ExclusivePart::Two, false, &options);
             }
         },
         Days::All => {
             for day in 1..=25 {
-                run_and_print_day(day, ExclusivePart::One, true);
-                run_and_print_day(day, ExclusivePart::Two, true);
+                run_and_print_day(day, ExclusivePart::One, true, &options);
+                run_and_print_day(day, ExclusivePart::Two, true, &options);
             }
         }
     }
@@ -32,9 +44,13 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn run_and_print_day(day: u32, part: ExclusivePart, hide_unimplemented: bool) {
+fn run_and_print_day(day: u32, part: ExclusivePart, hide_unimplemented: bool, options: &Options) {
+    let mut ctx = RunContext::new(options.verbosity, Box::new(io::stderr()))
+        .with_strategy(options.strategy.clone());
+    ctx.debug(format!("Day {day:>2}, part {part}:"));
+
     let now = Instant::now();
-    let run_opt = run(day, part);
+    let run_opt = run(day, part, &mut ctx);
     let elapsed = now.elapsed();
     let elasped_str = utils::format_duration(elapsed);
 
@@ -51,11 +67,64 @@ fn run_and_print_day(day: u32, part: ExclusivePart, hide_unimplemented: bool) {
             }
         },
     }
+
+    if options.strategy != StrategyChoice::Default
+        && !ctx.offered_strategies()
+        && !hide_unimplemented
+    {
+        eprintln!("Day {day:>2}, part {part} only has one strategy, so it was used");
+    }
+
+    if options.profile {
+        print_profile();
+    }
+}
+
+#[cfg(feature = "profile")]
+fn print_profile() {
+    print!("{}", utils::profile_report());
+    utils::reset_profile();
+}
+
+#[cfg(not(feature = "profile"))]
+fn print_profile() {}
+
+/// Split the command line into its flags and the arguments left over
+fn get_options(args: impl Iterator<Item = String>) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options::default();
+    let mut rest = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" if cfg!(feature = "profile") => options.profile = true,
+            "--profile" => {
+                return Err(
+                    "--profile needs timings compiled in, with `--features profile`".to_string(),
+                )
+            }
+            "-v" | "--verbose" => options.verbosity = Verbosity::Debug,
+            "-vv" => options.verbosity = Verbosity::Trace,
+            "--strategy" => {
+                let name = args
+                    .next_if(|name| !name.starts_with('-'))
+                    .ok_or("--strategy needs the name of a strategy")?;
+                options.strategy = StrategyChoice::Named(name);
+            }
+            "--cross-check" => options.strategy = StrategyChoice::CrossCheck,
+            flag if flag.starts_with("--strategy=") => {
+                let name = &flag["--strategy=".len()..];
+                options.strategy = StrategyChoice::Named(name.to_string());
+            }
+            flag if flag.starts_with('-') => return Err(format!("Unrecognized flag: {flag}")),
+            _ => rest.push(arg),
+        }
+    }
+    Ok((options, rest))
 }
 
 fn get_days(args: Vec<String>) -> Result<Days, String> {
-    if args.len() > 1 {
-        let mut day_components = args[1].split('.');
+    if let Some(day) = args.first() {
+        let mut day_components = day.split('.');
 
         let day_number = match day_components.next().unwrap().parse::<u32>() {
             Ok(n) => n,
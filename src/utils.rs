@@ -1,14 +1,47 @@
+mod boxes;
+pub use boxes::*;
+
+mod cycles;
+pub use cycles::*;
+
+mod distances;
+pub use distances::*;
+
+mod geometry;
+pub use geometry::*;
+
+mod graph;
+pub use graph::*;
+
 mod grid;
 pub use grid::*;
 
+mod grid_like;
+pub use grid_like::*;
+
 mod interval;
 pub use interval::*;
 
+mod interval_index;
+pub use interval_index::*;
+
 mod math;
 pub use math::*;
 
 mod parsing;
 pub use parsing::*;
 
+mod polygon;
+pub use polygon::*;
+
+mod search;
+pub use search::*;
+
+mod sparse_grid;
+pub use sparse_grid::*;
+
+mod tiled_grid;
+pub use tiled_grid::*;
+
 mod time;
 pub use time::*;
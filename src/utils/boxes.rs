@@ -0,0 +1,293 @@
+use std::{array, fmt, ops};
+
+/// An axis-aligned box of integer points in `N` dimensions
+///
+/// Along every axis the box covers `min..max`, so `max` itself is excluded. A box with
+/// nothing in it along any axis is empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HyperRect<const N: usize> {
+    min: [i64; N],
+    max: [i64; N],
+}
+
+impl<const N: usize> HyperRect<N> {
+    pub fn new(min: [i64; N], max: [i64; N]) -> Self {
+        Self { min, max }
+    }
+
+    /// The smallest box containing both corners, which may be given in any order
+    pub fn from_inclusive_corners(a: [i64; N], b: [i64; N]) -> Self {
+        Self {
+            min: array::from_fn(|axis| a[axis].min(b[axis])),
+            max: array::from_fn(|axis| a[axis].max(b[axis]) + 1),
+        }
+    }
+
+    pub fn from_ranges(ranges: [ops::Range<i64>; N]) -> Self {
+        Self {
+            min: array::from_fn(|axis| ranges[axis].start),
+            max: array::from_fn(|axis| ranges[axis].end),
+        }
+    }
+
+    pub fn min(&self) -> &[i64; N] {
+        &self.min
+    }
+
+    /// The corner just beyond the box on every axis
+    pub fn max(&self) -> &[i64; N] {
+        &self.max
+    }
+
+    /// The last point in the box on every axis
+    pub fn max_inclusive(&self) -> [i64; N] {
+        self.max.map(|max| max - 1)
+    }
+
+    pub fn range(&self, axis: usize) -> ops::Range<i64> {
+        self.min[axis]..self.max[axis]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|axis| self.min[axis] >= self.max[axis])
+    }
+
+    /// The number of points in the box, or `None` if that doesn't fit in a `u128`, which only
+    /// very large boxes in 3 or more dimensions can reach
+    pub fn volume(&self) -> Option<u128> {
+        if self.is_empty() {
+            return Some(0);
+        }
+        (0..N)
+            .map(|axis| (self.max[axis] as i128 - self.min[axis] as i128) as u128)
+            .try_fold(1u128, |volume, length| volume.checked_mul(length))
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        (0..N).all(|axis| self.min[axis] <= point[axis] && point[axis] < self.max[axis])
+    }
+
+    /// Whether any point is in both boxes
+    pub fn overlaps(&self, other: &Self) -> bool {
+        (0..N).all(|axis| self.min[axis].max(other.min[axis]) < self.max[axis].min(other.max[axis]))
+    }
+
+    /// The same box, moved by `offset`
+    pub fn translated(&self, offset: [i64; N]) -> Self {
+        Self {
+            min: array::from_fn(|axis| self.min[axis] + offset[axis]),
+            max: array::from_fn(|axis| self.max[axis] + offset[axis]),
+        }
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let intersection = Self {
+            min: array::from_fn(|axis| self.min[axis].max(other.min[axis])),
+            max: array::from_fn(|axis| self.max[axis].min(other.max[axis])),
+        };
+        (!intersection.is_empty()).then_some(intersection)
+    }
+
+    /// Everything in `self` which isn't in `other`, as disjoint boxes
+    ///
+    /// At most `2 * N` boxes are returned
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(intersection) = self.intersect(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+
+        // peel slabs off of each side of the intersection, one axis at a time
+        let mut pieces = Vec::new();
+        let mut remaining = *self;
+        for axis in 0..N {
+            let (below, rest) = remaining.split_at(axis, intersection.min[axis]);
+            let (middle, above) = match rest {
+                Some(rest) => rest.split_at(axis, intersection.max[axis]),
+                None => (None, None),
+            };
+            pieces.extend(below);
+            pieces.extend(above);
+            match middle {
+                Some(middle) => remaining = middle,
+                None => break,
+            }
+        }
+        pieces
+    }
+
+    /// Cut the box in two along `axis`, giving the parts below `value`, and at or above it
+    pub fn split_at(&self, axis: usize, value: i64) -> (Option<Self>, Option<Self>) {
+        let value = value.clamp(self.min[axis], self.max[axis].max(self.min[axis]));
+
+        let mut below = *self;
+        below.max[axis] = value;
+        let mut above = *self;
+        above.min[axis] = value;
+
+        (
+            (!below.is_empty()).then_some(below),
+            (!above.is_empty()).then_some(above),
+        )
+    }
+}
+
+impl<const N: usize> fmt::Display for HyperRect<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = (0..N).map(|axis| format!("{:?}", self.range(axis)));
+        write!(f, "[{}]", ranges.collect::<Vec<String>>().join(" x "))
+    }
+}
+
+/// A set of points in `N` dimensions, stored as boxes which never overlap
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DisjointBoxes<const N: usize> {
+    boxes: Vec<HyperRect<N>>,
+}
+
+impl<const N: usize> DisjointBoxes<N> {
+    pub fn new() -> Self {
+        Self { boxes: Vec::new() }
+    }
+
+    pub fn boxes(&self) -> &[HyperRect<N>] {
+        &self.boxes
+    }
+
+    pub fn len(&self) -> usize {
+        self.boxes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    /// Add every point in `rect` to the set
+    ///
+    /// Only the parts of `rect` which aren't already in the set are stored, so it may be cut into several boxes
+    pub fn insert(&mut self, rect: HyperRect<N>) {
+        let mut new_pieces = if rect.is_empty() { vec![] } else { vec![rect] };
+        for existing in self
+            .boxes
+            .iter()
+            .filter(|existing| existing.overlaps(&rect))
+        {
+            new_pieces = new_pieces
+                .iter()
+                .flat_map(|piece| piece.subtract(existing))
+                .collect();
+        }
+        self.boxes.extend(new_pieces);
+    }
+
+    /// Remove every point in `rect` from the set
+    pub fn remove(&mut self, rect: &HyperRect<N>) {
+        self.boxes = self
+            .boxes
+            .iter()
+            .flat_map(|existing| existing.subtract(rect))
+            .collect();
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.boxes.iter().any(|rect| rect.contains(point))
+    }
+
+    /// Every box in the set which shares a point with `rect`
+    pub fn overlapping<'a>(
+        &'a self,
+        rect: &'a HyperRect<N>,
+    ) -> impl Iterator<Item = &'a HyperRect<N>> + 'a {
+        self.boxes
+            .iter()
+            .filter(move |existing| existing.overlaps(rect))
+    }
+
+    /// The total number of points in the set, or `None` if that doesn't fit in a `u128`
+    pub fn volume(&self) -> Option<u128> {
+        self.boxes
+            .iter()
+            .try_fold(0u128, |total, rect| total.checked_add(rect.volume()?))
+    }
+}
+
+impl<const N: usize> FromIterator<HyperRect<N>> for DisjointBoxes<N> {
+    fn from_iter<I: IntoIterator<Item = HyperRect<N>>>(iter: I) -> Self {
+        let mut boxes = Self::new();
+        for rect in iter {
+            boxes.insert(rect);
+        }
+        boxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subtract_is_disjoint() {
+        let outer = HyperRect::new([0, 0, 0], [4, 4, 4]);
+        let inner = HyperRect::new([1, 1, 1], [3, 3, 3]);
+
+        let pieces = outer.subtract(&inner);
+
+        assert_eq!(pieces.len(), 6);
+        let volumes = pieces.iter().map(|piece| piece.volume().unwrap());
+        assert_eq!(volumes.sum::<u128>(), 64 - 8);
+        for (i, a) in pieces.iter().enumerate() {
+            assert!(!a.overlaps(&inner));
+            for b in &pieces[i + 1..] {
+                assert!(!a.overlaps(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_at() {
+        let rect = HyperRect::from_inclusive_corners([1, 1], [10, 5]);
+
+        let (below, above) = rect.split_at(0, 4);
+        assert_eq!(below, Some(HyperRect::new([1, 1], [4, 6])));
+        assert_eq!(above, Some(HyperRect::new([4, 1], [11, 6])));
+
+        // splitting outside the box leaves it whole on one side
+        assert_eq!(rect.split_at(1, 0), (None, Some(rect)));
+        assert_eq!(rect.split_at(1, 6), (Some(rect), None));
+
+        // long axes are cut just as quickly as short ones
+        let wide = HyperRect::new([i64::MIN], [i64::MAX]);
+        let (below, above) = wide.split_at(0, 0);
+        assert_eq!(below.unwrap().volume(), Some(1 << 63));
+        assert_eq!(above.unwrap().volume(), Some((1 << 63) - 1));
+    }
+
+    #[test]
+    fn test_volume_overflow() {
+        let huge = HyperRect::new([i64::MIN; 3], [i64::MAX; 3]);
+        assert_eq!(huge.volume(), None);
+        assert_eq!(DisjointBoxes::from_iter([huge]).volume(), None);
+
+        // boxes which each fit, but whose total doesn't
+        let slab = HyperRect::new([0, 0, 0], [1 << 62, 1 << 62, 8]);
+        assert_eq!(slab.volume(), Some(1 << 127));
+        let boxes = DisjointBoxes::from_iter((0..3).map(|i| slab.translated([0, 0, 8 * i])));
+        assert_eq!(boxes.volume(), None);
+    }
+
+    #[test]
+    fn test_disjoint_boxes_volume() {
+        let boxes = DisjointBoxes::from_iter([
+            HyperRect::new([0, 0], [3, 3]),
+            HyperRect::new([1, 1], [4, 4]),
+            HyperRect::new([1, 1], [2, 2]),
+        ]);
+
+        assert_eq!(boxes.volume(), Some(9 + 9 - 4));
+        assert!(boxes.contains(&[3, 3]));
+        assert!(!boxes.contains(&[0, 3]));
+        assert_eq!(
+            boxes.overlapping(&HyperRect::new([2, 2], [3, 3])).count(),
+            1
+        );
+    }
+}
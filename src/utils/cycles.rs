@@ -0,0 +1,400 @@
+use std::{collections::HashMap, convert::Infallible, hash::Hash};
+
+use num::Integer;
+
+/// The times at which something that eventually repeats hits a state we care about
+///
+/// Some hits may happen once, before the repeating part starts at `start`. From then on,
+/// the hits are `start + offset + k * period` for each offset and every `k >= 0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitTimes {
+    once: Vec<u64>,
+    start: u64,
+    period: u64,
+    offsets: Vec<u64>,
+}
+
+impl HitTimes {
+    /// Hits which happen at `once`, and then forever at the given offsets after `start`
+    ///
+    /// Every one of `once` must come before `start`, and every offset must be less than `period`
+    pub fn new(once: Vec<u64>, start: u64, period: u64, offsets: Vec<u64>) -> Result<Self, String> {
+        if period == 0 {
+            return Err("period of a cycle can't be zero".to_string());
+        }
+        if let Some(time) = once.iter().find(|&&time| time >= start) {
+            return Err(format!(
+                "one-off hit at {} must come before the cycle starts at {}",
+                time, start
+            ));
+        }
+        if let Some(offset) = offsets.iter().find(|&&offset| offset >= period) {
+            return Err(format!(
+                "offset {} doesn't fit in a cycle of period {}",
+                offset, period
+            ));
+        }
+
+        let mut once = once;
+        once.sort_unstable();
+        once.dedup();
+        let mut offsets = offsets;
+        offsets.sort_unstable();
+        offsets.dedup();
+
+        Ok(Self {
+            once,
+            start,
+            period,
+            offsets,
+        })
+    }
+
+    /// Hits at `offset`, `offset + period`, `offset + 2 * period`, and so on
+    pub fn periodic(offset: u64, period: u64) -> Result<Self, String> {
+        Self::new(vec![], offset, period, vec![0])
+    }
+
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    pub fn contains(&self, time: u64) -> bool {
+        if time < self.start {
+            self.once.binary_search(&time).is_ok()
+        } else {
+            self.offsets
+                .binary_search(&((time - self.start) % self.period))
+                .is_ok()
+        }
+    }
+
+    /// The earliest hit, if there are any at all
+    pub fn first(&self) -> Option<u64> {
+        self.once
+            .first()
+            .copied()
+            .or_else(|| self.offsets.first().map(|offset| self.start + offset))
+    }
+
+    /// The times at which both `self` and `other` hit
+    ///
+    /// Fails if the combined cycle would be longer than a `u64` can count
+    pub fn intersect(&self, other: &HitTimes) -> Result<HitTimes, String> {
+        let start = self.start.max(other.start);
+
+        // anything before both cycles have started has to be a one-off hit of one of them
+        let mut once = self
+            .once
+            .iter()
+            .chain(other.once.iter())
+            .copied()
+            .filter(|&time| time < start && self.contains(time) && other.contains(time))
+            .collect::<Vec<_>>();
+        once.sort_unstable();
+        once.dedup();
+
+        let period = (self.period / self.period.gcd(&other.period))
+            .checked_mul(other.period)
+            .ok_or_else(|| {
+                format!(
+                    "cycles of period {} and {} take too long to line up",
+                    self.period, other.period
+                )
+            })?;
+        let mut offsets = Vec::new();
+        for a in &self.offsets {
+            for b in &other.offsets {
+                let a = (self.start % self.period + a) % self.period;
+                let b = (other.start % other.period + b) % other.period;
+                if let Some((residue, _)) = crt((a, self.period), (b, other.period))? {
+                    // the residue and period both fit in a u64, but their sum might not
+                    let offset = (residue as u128 + period as u128 - (start % period) as u128)
+                        % period as u128;
+                    offsets.push(offset as u64);
+                }
+            }
+        }
+        offsets.sort_unstable();
+        offsets.dedup();
+
+        Ok(HitTimes {
+            once,
+            start,
+            period,
+            offsets,
+        })
+    }
+}
+
+/// Solve `t = a (mod m)` and `t = b (mod n)` together, even when `m` and `n` share factors
+///
+/// Gives `(t, lcm(m, n))` for the smallest non-negative `t`, or `None` if there is no solution.
+/// Fails if either modulus is zero, or if the lcm of the two moduli doesn't fit in a `u64`
+pub fn crt((a, m): (u64, u64), (b, n): (u64, u64)) -> Result<Option<(u64, u64)>, String> {
+    if m == 0 || n == 0 {
+        return Err("modulus must be positive".to_string());
+    }
+    let (a, m, b, n) = (a as i128, m as i128, b as i128, n as i128);
+
+    let gcd = m.extended_gcd(&n);
+    if (b - a) % gcd.gcd != 0 {
+        return Ok(None);
+    }
+
+    // m * x = gcd (mod n), so stepping a by m * x * (b - a) / gcd lands on b (mod n)
+    let reduced_n = n / gcd.gcd;
+    let steps = ((b - a) / gcd.gcd).rem_euclid(reduced_n) as u128
+        * gcd.x.rem_euclid(reduced_n) as u128
+        % reduced_n as u128;
+    let lcm = (m / gcd.gcd)
+        .checked_mul(n)
+        .filter(|&lcm| lcm <= u64::MAX as i128)
+        .ok_or_else(|| format!("lcm of {} and {} doesn't fit in a u64", m, n))?;
+    // steps < n / gcd, so m * steps < lcm
+    let t = (a + m * steps as i128).rem_euclid(lcm);
+
+    Ok(Some((t as u64, lcm as u64)))
+}
+
+/// The first time at which every one of `hits` happens together
+pub fn first_common_time(hits: &[HitTimes]) -> Result<u64, String> {
+    let (first, rest) = hits
+        .split_first()
+        .ok_or("can't find a common time without any cycles")?;
+
+    rest.iter()
+        .try_fold(first.clone(), |common, hits| common.intersect(hits))?
+        .first()
+        .ok_or_else(|| "the cycles never line up".to_string())
+}
+
+/// Where a sequence of states starts repeating
+///
+/// The states at steps `prefix_length..` repeat forever, every `period` steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix_length: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest step which is in the same state as step `n`
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.prefix_length {
+            n
+        } else {
+            self.prefix_length + (n - self.prefix_length) % self.period
+        }
+    }
+}
+
+/// Find the cycle in the states reached by repeatedly applying `step` to `start`,
+/// remembering every state seen along the way
+///
+/// Every sequence of states must eventually repeat, or this never returns
+pub fn find_cycle<S>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle
+where
+    S: Hash + Eq + Clone,
+{
+    let Ok(cycle) = try_find_cycle(start, |state| Ok::<_, Infallible>(step(state)));
+    cycle
+}
+
+/// Like [`find_cycle`], but stops at the first step which fails
+pub fn try_find_cycle<S, E>(start: S, mut step: impl FnMut(&S) -> Result<S, E>) -> Result<Cycle, E>
+where
+    S: Hash + Eq + Clone,
+{
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0.. {
+        if let Some(&first) = seen.get(&state) {
+            return Ok(Cycle {
+                prefix_length: first,
+                period: i - first,
+            });
+        }
+        let next = step(&state)?;
+        seen.insert(state, i);
+        state = next;
+    }
+    unreachable!("ran out of steps before finding a cycle")
+}
+
+/// Find the cycle using Brent's algorithm, which only ever holds on to two states
+///
+/// This needs a few more steps than [`find_cycle`], but works for states which can't be hashed,
+/// or are too big to keep all of them around
+pub fn find_cycle_brent<S>(start: S, step: impl Fn(&S) -> S) -> Cycle
+where
+    S: Eq + Clone,
+{
+    // find the period by sending the hare out on ever longer runs from the tortoise
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // then with the hare a whole period ahead, they first meet at the start of the cycle
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix_length = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix_length += 1;
+    }
+
+    Cycle {
+        prefix_length,
+        period,
+    }
+}
+
+/// The state after applying `step` to `start` `n` times, skipping ahead once the states repeat
+pub fn simulate_n<S>(start: S, mut step: impl FnMut(&S) -> S, n: usize) -> S
+where
+    S: Hash + Eq + Clone,
+{
+    let Ok(state) = try_simulate_n(start, |state| Ok::<_, Infallible>(step(state)), n);
+    state
+}
+
+/// Like [`simulate_n`], but stops at the first step which fails
+pub fn try_simulate_n<S, E>(
+    start: S,
+    mut step: impl FnMut(&S) -> Result<S, E>,
+    n: usize,
+) -> Result<S, E>
+where
+    S: Hash + Eq + Clone,
+{
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0..n {
+        if let Some(&first) = seen.get(&state) {
+            // we're back where we were at step `first`, so we can jump ahead by whole periods
+            let remaining = (n - i) % (i - first);
+            for _ in 0..remaining {
+                state = step(&state)?;
+            }
+            return Ok(state);
+        }
+        let next = step(&state)?;
+        seen.insert(state, i);
+        state = next;
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Ok(Some((8, 15))));
+        assert_eq!(crt((1, 4), (3, 6)), Ok(Some((9, 12))));
+        assert_eq!(crt((0, 4), (1, 6)), Ok(None));
+
+        assert!(crt((0, 0), (1, 6)).is_err());
+        assert!(crt((0, 4), (0, 0)).is_err());
+        assert!(HitTimes::periodic(3, 0).is_err());
+    }
+
+    #[test]
+    fn test_crt_overflow() {
+        let big_prime = 18446744073709551557;
+        assert!(crt((0, big_prime), (1, 3)).is_err());
+        // the lcm only just fits when one modulus divides the other
+        assert_eq!(crt((5, u64::MAX), (0, 5)), Ok(Some((5, u64::MAX))));
+
+        let hits = [
+            HitTimes::periodic(0, big_prime).unwrap(),
+            HitTimes::periodic(1, 3).unwrap(),
+        ];
+        assert!(first_common_time(&hits).is_err());
+    }
+
+    #[test]
+    fn test_aligned_cycles_match_lcm() {
+        let hits = [
+            HitTimes::periodic(4, 4).unwrap(),
+            HitTimes::periodic(6, 6).unwrap(),
+            HitTimes::periodic(10, 10).unwrap(),
+        ];
+        assert_eq!(first_common_time(&hits), Ok(60));
+    }
+
+    #[test]
+    fn test_unaligned_cycles() {
+        let hits = [
+            HitTimes::periodic(2, 3).unwrap(),
+            HitTimes::periodic(3, 5).unwrap(),
+        ];
+        assert_eq!(first_common_time(&hits), Ok(8));
+
+        // the one-off hit at 1 is the only time these line up
+        let hits = [
+            HitTimes::new(vec![1], 2, 2, vec![0]).unwrap(),
+            HitTimes::periodic(1, 4).unwrap(),
+        ];
+        assert_eq!(first_common_time(&hits), Ok(1));
+
+        let never = [
+            HitTimes::periodic(0, 4).unwrap(),
+            HitTimes::periodic(1, 6).unwrap(),
+        ];
+        assert!(first_common_time(&never).is_err());
+    }
+
+    #[test]
+    fn test_several_hits_per_cycle() {
+        // hits at 3, 5, 10, 12, ...
+        let a = HitTimes::new(vec![], 3, 7, vec![0, 2]).unwrap();
+        // hits at 0, 4, 8, 12, ...
+        let b = HitTimes::periodic(0, 4).unwrap();
+
+        assert_eq!(first_common_time(&[a, b]), Ok(12));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        // 0, 1, 2, 3, 4, 5, 3, 4, 5, ...
+        let step = |&state: &u32| if state == 5 { 3 } else { state + 1 };
+
+        let expected = Cycle {
+            prefix_length: 3,
+            period: 3,
+        };
+        assert_eq!(find_cycle(0, step), expected);
+        assert_eq!(find_cycle_brent(0, step), expected);
+        assert_eq!(expected.equivalent_step(1_000), 4);
+    }
+
+    #[test]
+    fn test_simulate_n_matches_stepping() {
+        let step = |&state: &u64| (state * state + 1) % 1_009;
+
+        for n in [0, 1, 10, 100, 1_000] {
+            let mut state = 2;
+            for _ in 0..n {
+                state = step(&state);
+            }
+            assert_eq!(simulate_n(2, step, n), state);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+
+use super::{Grid, GridLike, GridPoint, SparseGrid, TiledGrid};
+
+impl<T> Grid<T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    /// Compute the number of orthogonal steps from `start` to every point in the grid
+    ///
+    /// Points that can't be reached are left as `None`
+    pub fn bfs_distances(
+        &self,
+        start: &GridPoint,
+        traversable: impl Fn(&T) -> bool,
+    ) -> Grid<Option<u64>> {
+        self.bfs_distances_multi([*start], traversable)
+    }
+
+    /// Like [`Grid::bfs_distances`], but measures the distance to the nearest of several starting points
+    pub fn bfs_distances_multi(
+        &self,
+        starts: impl IntoIterator<Item = GridPoint>,
+        traversable: impl Fn(&T) -> bool,
+    ) -> Grid<Option<u64>> {
+        self.bfs_distances_within(starts, traversable, u64::MAX)
+    }
+
+    /// Like [`Grid::bfs_distances_multi`], but stops exploring once `max_distance` steps away
+    pub fn bfs_distances_within(
+        &self,
+        starts: impl IntoIterator<Item = GridPoint>,
+        traversable: impl Fn(&T) -> bool,
+        max_distance: u64,
+    ) -> Grid<Option<u64>> {
+        let mut distances = Grid::new_empty(self.width(), self.height());
+        distances.set_top_left(*self.top_left());
+
+        fill_bfs_distances(&mut distances, starts, max_distance, |point| {
+            self.get(point).is_some_and(&traversable)
+        });
+
+        distances
+    }
+}
+
+impl<'a, T> TiledGrid<'a, T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    /// Compute the number of orthogonal steps from the nearest of `starts` to every point
+    /// on the infinite plane that is at most `max_distance` steps away
+    pub fn bfs_distances_within(
+        &self,
+        starts: impl IntoIterator<Item = GridPoint>,
+        traversable: impl Fn(&T) -> bool,
+        max_distance: u64,
+    ) -> SparseGrid<Option<u64>> {
+        let mut distances = SparseGrid::new();
+
+        fill_bfs_distances(&mut distances, starts, max_distance, |point| {
+            self.get(point).is_some_and(&traversable)
+        });
+
+        distances
+    }
+}
+
+/// Breadth-first search outwards from `starts`, recording the distance to every point reached
+///
+/// Starting points which can't be entered are ignored
+fn fill_bfs_distances(
+    distances: &mut impl GridLike<Option<u64>>,
+    starts: impl IntoIterator<Item = GridPoint>,
+    max_distance: u64,
+    can_enter: impl Fn(&GridPoint) -> bool,
+) {
+    let mut frontier = VecDeque::new();
+    for start in starts {
+        if can_enter(&start) && distances.distance(&start).is_none() {
+            distances
+                .set(&start, Some(0))
+                .expect("enterable points are always within bounds");
+            frontier.push_back((start, 0));
+        }
+    }
+
+    while let Some((point, distance)) = frontier.pop_front() {
+        if distance >= max_distance {
+            continue;
+        }
+        for neighbor in point.orthogonal_neighbors() {
+            if can_enter(&neighbor) && distances.distance(&neighbor).is_none() {
+                distances
+                    .set(&neighbor, Some(distance + 1))
+                    .expect("enterable points are always within bounds");
+                frontier.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+}
+
+/// Queries over the result of a breadth-first distance search
+///
+/// This is implemented for every grid of `Option<u64>`, where `None` marks unreachable points
+pub trait DistanceMap: GridLike<Option<u64>> {
+    fn distance(&self, point: &GridPoint) -> Option<u64> {
+        self.get(point).copied().flatten()
+    }
+
+    /// Every reachable point at most `max_distance` steps away
+    fn reachable_within(&self, max_distance: u64) -> impl Iterator<Item = GridPoint> {
+        self.entries().filter_map(move |entry| match entry.value {
+            Some(distance) if *distance <= max_distance => Some(entry.point),
+            _ => None,
+        })
+    }
+
+    /// Every point that could be standing on after exactly `steps` steps, given that
+    /// stepping back and forth is allowed
+    ///
+    /// That is any point at most `steps` away whose distance has the same parity as `steps`
+    fn reachable_with_parity(&self, steps: u64) -> impl Iterator<Item = GridPoint> {
+        self.entries().filter_map(move |entry| match entry.value {
+            Some(distance) if *distance <= steps && distance % 2 == steps % 2 => Some(entry.point),
+            _ => None,
+        })
+    }
+
+    fn num_reachable_with_parity(&self, steps: u64) -> usize {
+        self.reachable_with_parity(steps).count()
+    }
+}
+
+impl<G> DistanceMap for G where G: GridLike<Option<u64>> {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const GARDEN: &str = "\
+.....
+.##..
+..#..
+.....";
+
+    fn garden() -> Grid<char> {
+        Grid::from_data(GARDEN.lines().map(|line| line.chars().collect()).collect()).unwrap()
+    }
+
+    fn is_open(c: &char) -> bool {
+        *c == '.'
+    }
+
+    fn rows(distances: &Grid<Option<u64>>) -> Vec<String> {
+        distances
+            .rows_iter()
+            .map(|row| {
+                row.map(|distance| {
+                    distance.map_or('#', |d| char::from_digit(d as u32, 36).unwrap())
+                })
+                .collect()
+            })
+            .collect()
+    }
+
+    /// Every point that can be stood on after exactly `steps` steps, by trying every step
+    fn simulate(start: GridPoint, steps: u64, can_enter: impl Fn(&GridPoint) -> bool) -> usize {
+        let mut positions = HashSet::from([start]);
+        for _ in 0..steps {
+            positions = positions
+                .iter()
+                .flat_map(|point| point.orthogonal_neighbors())
+                .filter(|point| can_enter(point))
+                .collect();
+        }
+        positions.len()
+    }
+
+    #[test]
+    fn test_bfs_distances_multi() {
+        let garden = garden();
+
+        let distances = garden.bfs_distances(&(0, 0).into(), is_open);
+        assert_eq!(rows(&distances), ["01234", "1##45", "23#56", "34567"]);
+
+        // every point is as far as the nearer of the two starts, and walls are never reached
+        let distances = garden.bfs_distances_multi([(0, 0).into(), (4, 3).into()], is_open);
+        assert_eq!(rows(&distances), ["01233", "1##32", "23#21", "33210"]);
+
+        // starts on a wall are ignored
+        let distances = garden.bfs_distances_multi([(1, 1).into()], is_open);
+        assert_eq!(distances.reachable_within(u64::MAX).count(), 0);
+    }
+
+    #[test]
+    fn test_bfs_distances_within() {
+        let distances = garden().bfs_distances_within([(0, 0).into()], is_open, 2);
+        assert_eq!(rows(&distances), ["012##", "1####", "2####", "#####"]);
+        assert_eq!(distances.reachable_within(1).count(), 3);
+    }
+
+    #[test]
+    fn test_parity_matches_simulation() {
+        let garden = garden();
+        let start = GridPoint::from((3, 2));
+        let distances = garden.bfs_distances(&start, is_open);
+
+        for steps in 0..12 {
+            let expected = simulate(start, steps, |point| garden.get(point).is_some_and(is_open));
+            assert_eq!(
+                distances.num_reachable_with_parity(steps),
+                expected,
+                "{steps} steps"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tiled_parity_matches_simulation() {
+        let garden = garden();
+        let tiled = TiledGrid::new(&garden);
+        let start = GridPoint::from((3, 2));
+
+        for steps in 0..16 {
+            let distances = tiled.bfs_distances_within([start], is_open, steps);
+            let expected = simulate(start, steps, |point| tiled.get(point).is_some_and(is_open));
+            assert_eq!(
+                distances.num_reachable_with_parity(steps),
+                expected,
+                "{steps} steps"
+            );
+        }
+    }
+}
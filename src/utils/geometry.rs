@@ -0,0 +1,225 @@
+use std::{array, fmt};
+
+use num::{BigInt, BigRational, Zero};
+
+/// A point moving in a straight line at a constant integer velocity, starting from `origin`
+/// at time zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ray<const N: usize> {
+    origin: [i64; N],
+    velocity: [i64; N],
+}
+
+/// Where the paths of two rays in a plane cross, ignoring when each ray gets there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathIntersection {
+    /// The paths cross at a single point, which each ray reaches at its own time
+    Point {
+        time_a: BigRational,
+        time_b: BigRational,
+    },
+    /// The paths are parallel and never meet
+    Parallel,
+    /// The paths lie along the same line
+    Collinear,
+}
+
+impl<const N: usize> Ray<N> {
+    pub fn new(origin: [i64; N], velocity: [i64; N]) -> Self {
+        Self { origin, velocity }
+    }
+
+    pub fn origin(&self) -> &[i64; N] {
+        &self.origin
+    }
+
+    pub fn velocity(&self) -> &[i64; N] {
+        &self.velocity
+    }
+
+    /// Exactly where the ray is at time `t`
+    pub fn position_at(&self, t: &BigRational) -> [BigRational; N] {
+        array::from_fn(|axis| rational(self.origin[axis]) + t * rational(self.velocity[axis]))
+    }
+
+    /// The time at which both rays are at the same point at the same time, if they ever are
+    ///
+    /// Identical rays are together at every time, so this gives time zero for them
+    pub fn collision_time(&self, other: &Self) -> Option<BigRational> {
+        // origin_a + t * velocity_a = origin_b + t * velocity_b, on every axis at once
+        let mut time: Option<BigRational> = None;
+        for axis in 0..N {
+            let distance = other.origin[axis] as i128 - self.origin[axis] as i128;
+            let closing_speed = self.velocity[axis] as i128 - other.velocity[axis] as i128;
+            if closing_speed == 0 {
+                if distance != 0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let axis_time = BigRational::new(BigInt::from(distance), BigInt::from(closing_speed));
+            match &time {
+                Some(time) if *time != axis_time => return None,
+                _ => time = Some(axis_time),
+            }
+        }
+        Some(time.unwrap_or_else(BigRational::zero))
+    }
+}
+
+impl Ray<2> {
+    /// Where the lines traced out by the two rays cross, with the time each ray reaches that
+    /// point, which may be negative if it happened before time zero
+    pub fn path_intersection(&self, other: &Self) -> PathIntersection {
+        let [ax, ay] = self.velocity.map(i128::from);
+        let [bx, by] = other.velocity.map(i128::from);
+        let dx = other.origin[0] as i128 - self.origin[0] as i128;
+        let dy = other.origin[1] as i128 - self.origin[1] as i128;
+
+        // solve origin_a + time_a * velocity_a = origin_b + time_b * velocity_b with
+        // Cramer's rule, where every determinant is a 2D cross product
+        let determinant = ax * by - ay * bx;
+        let cross_with_a = dx * ay - dy * ax;
+        if determinant == 0 {
+            return if cross_with_a == 0 {
+                PathIntersection::Collinear
+            } else {
+                PathIntersection::Parallel
+            };
+        }
+
+        let determinant = BigInt::from(determinant);
+        let time_a = BigRational::new(BigInt::from(dx * by - dy * bx), determinant.clone());
+        let time_b = BigRational::new(BigInt::from(cross_with_a), determinant);
+
+        PathIntersection::Point { time_a, time_b }
+    }
+}
+
+impl<const N: usize> fmt::Display for Ray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |values: &[i64; N]| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(f, "{} @ {}", join(&self.origin), join(&self.velocity))
+    }
+}
+
+/// Solve `matrix * x = rhs` exactly, with Gaussian elimination
+///
+/// The matrix must be square, and gives an error if it is singular
+pub fn solve_linear_system(
+    mut matrix: Vec<Vec<BigRational>>,
+    mut rhs: Vec<BigRational>,
+) -> Result<Vec<BigRational>, String> {
+    let size = matrix.len();
+    if rhs.len() != size || matrix.iter().any(|row| row.len() != size) {
+        return Err(format!(
+            "expected a square system, but got {} equations in {} unknowns",
+            rhs.len(),
+            matrix.first().map_or(0, Vec::len)
+        ));
+    }
+
+    for column in 0..size {
+        let pivot = (column..size)
+            .find(|&row| !matrix[row][column].is_zero())
+            .ok_or("linear system is singular, so it has no unique solution")?;
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        for row in 0..size {
+            if row == column || matrix[row][column].is_zero() {
+                continue;
+            }
+            let factor = &matrix[row][column] / &matrix[column][column];
+            let pivot_row = matrix[column].clone();
+            for (value, pivot_value) in matrix[row].iter_mut().zip(&pivot_row).skip(column) {
+                *value -= &factor * pivot_value;
+            }
+            let delta = &factor * &rhs[column];
+            rhs[row] -= delta;
+        }
+    }
+
+    Ok((0..size).map(|i| &rhs[i] / &matrix[i][i]).collect())
+}
+
+fn rational(value: i64) -> BigRational {
+    BigRational::from_integer(BigInt::from(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_intersection() {
+        let a = Ray::new([0, 0], [1, 1]);
+        let b = Ray::new([10, 0], [-2, 2]);
+        let PathIntersection::Point { time_a, time_b } = a.path_intersection(&b) else {
+            panic!("paths should cross");
+        };
+        assert_eq!(a.position_at(&time_a), [rational(5), rational(5)]);
+        assert_eq!(time_a, rational(5));
+        assert_eq!(time_b, BigRational::new(5.into(), 2.into()));
+
+        assert_eq!(
+            a.path_intersection(&Ray::new([0, 1], [2, 2])),
+            PathIntersection::Parallel
+        );
+        assert_eq!(
+            a.path_intersection(&Ray::new([3, 3], [-1, -1])),
+            PathIntersection::Collinear
+        );
+    }
+
+    #[test]
+    fn test_path_intersection_keeps_precision() {
+        // at this size, an f64 can't tell these crossing points apart
+        let big = 400_000_000_000_000;
+        let a = Ray::new([big, big], [-1, 0]);
+        let b = Ray::new([big - 1, 0], [0, 3]);
+        let PathIntersection::Point { time_a, time_b } = a.path_intersection(&b) else {
+            panic!("paths should cross");
+        };
+        assert_eq!(a.position_at(&time_a), [rational(big - 1), rational(big)]);
+        assert_eq!(time_b, BigRational::new(big.into(), 3.into()));
+    }
+
+    #[test]
+    fn test_collision_time() {
+        let a = Ray::new([0, 0, 0], [1, 2, 3]);
+        assert_eq!(
+            a.collision_time(&Ray::new([4, 0, 8], [0, 2, 1])),
+            Some(rational(4))
+        );
+        assert_eq!(a.collision_time(&Ray::new([4, 1, 8], [0, 2, 1])), None);
+        assert_eq!(a.collision_time(&a), Some(rational(0)));
+    }
+
+    #[test]
+    fn test_solve_linear_system() {
+        let matrix = vec![
+            vec![rational(2), rational(1), rational(-1)],
+            vec![rational(-3), rational(-1), rational(2)],
+            vec![rational(-2), rational(1), rational(2)],
+        ];
+        let rhs = vec![rational(8), rational(-11), rational(-3)];
+        assert_eq!(
+            solve_linear_system(matrix, rhs),
+            Ok(vec![rational(2), rational(3), rational(-1)])
+        );
+
+        let singular = vec![
+            vec![rational(1), rational(2)],
+            vec![rational(2), rational(4)],
+        ];
+        assert!(solve_linear_system(singular, vec![rational(1), rational(2)]).is_err());
+    }
+}
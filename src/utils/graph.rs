@@ -0,0 +1,614 @@
+use std::{
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// A directed graph whose nodes are labeled by `N` and edges by `E`
+///
+/// Every node label is unique, and nodes are referred to by the index they were added at.
+/// Parallel edges and self-loops are allowed
+#[derive(Debug, Clone)]
+pub struct DiGraph<N, E> {
+    nodes: Vec<N>,
+    indices: HashMap<N, usize>,
+    edges: Vec<Edge<E>>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge<E> {
+    pub from: usize,
+    pub to: usize,
+    pub label: E,
+}
+
+/// A split of a graph's nodes into two sides, and the total weight of the edges between them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    pub weight: u64,
+    /// The nodes on one side of the cut, with every other node on the other side
+    pub side: Vec<usize>,
+}
+
+impl<N, E> DiGraph<N, E>
+where
+    N: Hash + Eq + Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            edges: Vec::new(),
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        }
+    }
+
+    /// Add a node, or find the one that already has this label
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(&index) = self.indices.get(&node) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.indices.insert(node.clone(), index);
+        self.nodes.push(node);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        index
+    }
+
+    /// Add an edge between two nodes, adding the nodes too if they are new
+    pub fn add_edge(&mut self, from: N, to: N, label: E) -> usize {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.add_edge_between(from, to, label)
+    }
+
+    /// Add an edge between two nodes which are already in the graph
+    pub fn add_edge_between(&mut self, from: usize, to: usize, label: E) -> usize {
+        let index = self.edges.len();
+        self.edges.push(Edge { from, to, label });
+        self.outgoing[from].push(index);
+        self.incoming[to].push(index);
+        index
+    }
+
+    pub fn index_of(&self, node: &N) -> Option<usize> {
+        self.indices.get(node).copied()
+    }
+
+    pub fn node(&self, index: usize) -> &N {
+        &self.nodes[index]
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge<E>] {
+        &self.edges
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn out_edges(&self, node: usize) -> impl Iterator<Item = &Edge<E>> {
+        self.outgoing[node].iter().map(|&edge| &self.edges[edge])
+    }
+
+    pub fn in_edges(&self, node: usize) -> impl Iterator<Item = &Edge<E>> {
+        self.incoming[node].iter().map(|&edge| &self.edges[edge])
+    }
+
+    pub fn successors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.out_edges(node).map(|edge| edge.to)
+    }
+
+    pub fn predecessors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.in_edges(node).map(|edge| edge.from)
+    }
+
+    /// Every node reachable from `start`, in breadth-first order
+    pub fn bfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for next in self.successors(node) {
+                if !visited[next] {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        order
+    }
+
+    /// Every node reachable from `start`, in depth-first preorder
+    pub fn dfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            order.push(node);
+            // push in reverse so the first successor is visited first
+            let successors = self.successors(node).collect::<Vec<_>>();
+            stack.extend(successors.into_iter().rev().filter(|&next| !visited[next]));
+        }
+        order
+    }
+
+    /// Whether any path leads from `from` to `to`
+    pub fn is_reachable(&self, from: usize, to: usize) -> bool {
+        self.bfs(from).contains(&to)
+    }
+
+    /// Every node in an order where edges only ever point forwards,
+    /// or an error if the graph has a cycle
+    pub fn topological_order(&self) -> Result<Vec<usize>, String> {
+        let mut in_degrees = self
+            .incoming
+            .iter()
+            .map(|edges| edges.len())
+            .collect::<Vec<_>>();
+        let mut ready = (0..self.len())
+            .filter(|&node| in_degrees[node] == 0)
+            .collect::<VecDeque<_>>();
+
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for next in self.successors(node) {
+                in_degrees[next] -= 1;
+                if in_degrees[next] == 0 {
+                    ready.push_back(next);
+                }
+            }
+        }
+
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err("graph has a cycle, so it has no topological order".to_string())
+        }
+    }
+
+    /// Group the nodes into strongly connected components, where every node in a component can
+    /// reach every other
+    ///
+    /// Uses Tarjan's algorithm, so the components come out in reverse topological order
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let mut index = vec![UNVISITED; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+
+        for root in 0..self.len() {
+            if index[root] != UNVISITED {
+                continue;
+            }
+
+            // each frame is a node along with how many of its edges we've looked at so far
+            let mut frames = vec![(root, 0)];
+            index[root] = next_index;
+            low_link[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&mut (node, ref mut edge)) = frames.last_mut() {
+                if let Some(&edge_index) = self.outgoing[node].get(*edge) {
+                    *edge += 1;
+                    let next = self.edges[edge_index].to;
+                    if index[next] == UNVISITED {
+                        index[next] = next_index;
+                        low_link[next] = next_index;
+                        next_index += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        frames.push((next, 0));
+                    } else if on_stack[next] {
+                        low_link[node] = low_link[node].min(index[next]);
+                    }
+                    continue;
+                }
+
+                // every edge is done, so pop this node and pass its low link up to its parent
+                frames.pop();
+                if let Some(&(parent, _)) = frames.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// The immediate dominator of every node reachable from `root`
+    ///
+    /// A node's dominators are the nodes every path from `root` to it has to pass through.
+    /// `root` is its own immediate dominator, and unreachable nodes have none
+    pub fn dominators(&self, root: usize) -> Vec<Option<usize>> {
+        // Cooper, Harvey and Kennedy's iterative algorithm, working in reverse postorder
+        let postorder = self.postorder(root);
+        let mut postorder_number = vec![None; self.len()];
+        for (number, &node) in postorder.iter().enumerate() {
+            postorder_number[node] = Some(number);
+        }
+
+        let mut idom = vec![None; self.len()];
+        idom[root] = Some(root);
+
+        let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+            while a != b {
+                while postorder_number[a] < postorder_number[b] {
+                    a = idom[a].expect("processed nodes have a dominator");
+                }
+                while postorder_number[b] < postorder_number[a] {
+                    b = idom[b].expect("processed nodes have a dominator");
+                }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node in postorder.iter().rev().filter(|&&node| node != root) {
+                let new_idom = self
+                    .predecessors(node)
+                    .filter(|&predecessor| idom[predecessor].is_some())
+                    .reduce(|a, b| intersect(&idom, a, b));
+                if new_idom.is_some() && idom[node] != new_idom {
+                    idom[node] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        idom
+    }
+
+    fn postorder(&self, root: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut frames = vec![(root, 0)];
+        visited[root] = true;
+        while let Some(&mut (node, ref mut edge)) = frames.last_mut() {
+            if let Some(&edge_index) = self.outgoing[node].get(*edge) {
+                *edge += 1;
+                let next = self.edges[edge_index].to;
+                if !visited[next] {
+                    visited[next] = true;
+                    frames.push((next, 0));
+                }
+            } else {
+                frames.pop();
+                order.push(node);
+            }
+        }
+        order
+    }
+
+    /// The most flow that can get from `source` to `sink`, where each edge carries at most its
+    /// capacity in its own direction
+    ///
+    /// Uses Edmonds-Karp. The flow is also the weight of the smallest cut separating the two, so
+    /// this returns that cut, with `source` on the listed side
+    pub fn max_flow(&self, source: usize, sink: usize, capacity: impl Fn(&E) -> u64) -> Cut {
+        // residual arcs come in pairs, so arc `i ^ 1` always runs the opposite way to arc `i`
+        let mut arc_to = Vec::with_capacity(self.edges.len() * 2);
+        let mut residual = Vec::with_capacity(self.edges.len() * 2);
+        let mut arcs_from = vec![Vec::new(); self.len()];
+        for edge in &self.edges {
+            arcs_from[edge.from].push(arc_to.len());
+            arc_to.push(edge.to);
+            residual.push(capacity(&edge.label));
+            arcs_from[edge.to].push(arc_to.len());
+            arc_to.push(edge.from);
+            residual.push(0);
+        }
+
+        let mut flow = 0;
+        loop {
+            // find the shortest path with room left along it
+            let mut parent_arc = vec![None; self.len()];
+            let mut reached = vec![false; self.len()];
+            let mut queue = VecDeque::from([source]);
+            reached[source] = true;
+            while let Some(node) = queue.pop_front() {
+                for &arc in &arcs_from[node] {
+                    let next = arc_to[arc];
+                    if !reached[next] && residual[arc] > 0 {
+                        reached[next] = true;
+                        parent_arc[next] = Some(arc);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            if source == sink || !reached[sink] {
+                let side = (0..self.len()).filter(|&node| reached[node]).collect();
+                return Cut { weight: flow, side };
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+            while let Some(arc) = parent_arc[node] {
+                path.push(arc);
+                node = arc_to[arc ^ 1];
+            }
+            let bottleneck = path.iter().map(|&arc| residual[arc]).min().unwrap_or(0);
+            for arc in path {
+                residual[arc] -= bottleneck;
+                residual[arc ^ 1] += bottleneck;
+            }
+            flow += bottleneck;
+        }
+    }
+
+    /// The lightest cut splitting the graph in two, treating every edge as undirected,
+    /// or `None` if there are fewer than two nodes to split
+    ///
+    /// Uses the Stoer-Wagner algorithm, so it's deterministic and needs no source or sink
+    pub fn min_cut(&self, weight: impl Fn(&E) -> u64) -> Option<Cut> {
+        if self.len() < 2 {
+            return None;
+        }
+
+        let mut adjacency = vec![HashMap::new(); self.len()];
+        for edge in self.edges.iter().filter(|edge| edge.from != edge.to) {
+            let weight = weight(&edge.label);
+            *adjacency[edge.from].entry(edge.to).or_insert(0) += weight;
+            *adjacency[edge.to].entry(edge.from).or_insert(0) += weight;
+        }
+        // the original nodes which have been merged into each node so far
+        let mut members = (0..self.len()).map(|node| vec![node]).collect::<Vec<_>>();
+        let mut active = (0..self.len()).collect::<Vec<_>>();
+
+        let mut best: Option<Cut> = None;
+        while active.len() > 1 {
+            // add nodes one at a time, always taking the one most tightly connected to those
+            // added so far. cutting off the last one added gives the lightest cut between it
+            // and the one before
+            let mut added = vec![false; self.len()];
+            let mut connection = vec![0; self.len()];
+            let mut heap = BinaryHeap::new();
+            let mut order = Vec::with_capacity(active.len());
+            let mut last_connection = 0;
+            while order.len() < active.len() {
+                let (weight, node) = match heap.pop() {
+                    Some(entry) => entry,
+                    // anything left is disconnected from what we've added so far
+                    None => (0, *active.iter().find(|&&node| !added[node])?),
+                };
+                if added[node] || weight != connection[node] {
+                    continue;
+                }
+                added[node] = true;
+                order.push(node);
+                last_connection = weight;
+                for (&next, &edge_weight) in &adjacency[node] {
+                    if !added[next] {
+                        connection[next] += edge_weight;
+                        heap.push((connection[next], next));
+                    }
+                }
+            }
+
+            let last = order[order.len() - 1];
+            let previous = order[order.len() - 2];
+            if best
+                .as_ref()
+                .is_none_or(|best| last_connection < best.weight)
+            {
+                best = Some(Cut {
+                    weight: last_connection,
+                    side: members[last].clone(),
+                });
+            }
+
+            // merge the last node into the one before it
+            let last_edges = std::mem::take(&mut adjacency[last]);
+            for (next, edge_weight) in last_edges {
+                adjacency[next].remove(&last);
+                if next != previous {
+                    *adjacency[previous].entry(next).or_insert(0) += edge_weight;
+                    *adjacency[next].entry(previous).or_insert(0) += edge_weight;
+                }
+            }
+            let last_members = std::mem::take(&mut members[last]);
+            members[previous].extend(last_members);
+            active.retain(|&node| node != last);
+        }
+
+        best
+    }
+
+    /// Render the graph in Graphviz's DOT language
+    ///
+    /// Edges without a label are drawn as bare arrows
+    pub fn to_dot(
+        &self,
+        node_label: impl Fn(&N) -> String,
+        edge_label: impl Fn(&E) -> Option<String>,
+    ) -> String {
+        let escape = |label: String| label.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut dot = String::from("digraph {\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            dot.push_str(&format!(
+                "    {} [label=\"{}\"];\n",
+                index,
+                escape(node_label(node))
+            ));
+        }
+        for edge in &self.edges {
+            match edge_label(&edge.label) {
+                Some(label) => dot.push_str(&format!(
+                    "    {} -> {} [label=\"{}\"];\n",
+                    edge.from,
+                    edge.to,
+                    escape(label)
+                )),
+                None => dot.push_str(&format!("    {} -> {};\n", edge.from, edge.to)),
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<N, E> Default for DiGraph<N, E>
+where
+    N: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E> FromIterator<(N, N, E)> for DiGraph<N, E>
+where
+    N: Hash + Eq + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (N, N, E)>>(iter: I) -> Self {
+        let mut graph = Self::new();
+        for (from, to, label) in iter {
+            graph.add_edge(from, to, label);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&'static str, &'static str)]) -> DiGraph<&'static str, ()> {
+        edges.iter().map(|&(from, to)| (from, to, ())).collect()
+    }
+
+    fn labels(graph: &DiGraph<&'static str, ()>, nodes: &[usize]) -> Vec<&'static str> {
+        nodes.iter().map(|&node| *graph.node(node)).collect()
+    }
+
+    #[test]
+    fn test_traversal_and_topological_order() {
+        let g = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")]);
+        let a = g.index_of(&"a").unwrap();
+
+        assert_eq!(labels(&g, &g.bfs(a)), vec!["a", "b", "c", "d"]);
+        assert_eq!(labels(&g, &g.dfs(a)), vec!["a", "b", "d", "c"]);
+        assert!(g.is_reachable(a, g.index_of(&"d").unwrap()));
+        assert!(!g.is_reachable(g.index_of(&"d").unwrap(), a));
+        assert_eq!(
+            labels(&g, &g.topological_order().unwrap()),
+            vec!["a", "b", "c", "d"]
+        );
+
+        let cyclic = graph(&[("a", "b"), ("b", "a")]);
+        assert!(cyclic.topological_order().is_err());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let g = graph(&[
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "d"),
+        ]);
+
+        let mut components = g
+            .strongly_connected_components()
+            .iter()
+            .map(|component| {
+                let mut component = labels(&g, component);
+                component.sort();
+                component
+            })
+            .collect::<Vec<_>>();
+        // the sink component comes out first
+        assert_eq!(components.remove(0), vec!["d", "e"]);
+        assert_eq!(components, vec![vec!["a", "b", "c"]]);
+    }
+
+    #[test]
+    fn test_dominators() {
+        // r -> a -> c, r -> b -> c, c -> d
+        let g = graph(&[("r", "a"), ("r", "b"), ("a", "c"), ("b", "c"), ("c", "d")]);
+        let index = |label| g.index_of(&label).unwrap();
+
+        let idom = g.dominators(index("r"));
+        assert_eq!(idom[index("a")], Some(index("r")));
+        assert_eq!(idom[index("c")], Some(index("r")));
+        assert_eq!(idom[index("d")], Some(index("c")));
+    }
+
+    #[test]
+    fn test_cuts() {
+        // two triangles, joined by a single bridge from c to d
+        let g = graph(&[
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("f", "d"),
+        ]);
+        let index = |label| g.index_of(&label).unwrap();
+
+        let cut = g.min_cut(|_| 1).unwrap();
+        assert_eq!(cut.weight, 1);
+        let mut side = labels(&g, &cut.side);
+        side.sort();
+        assert!(side == vec!["a", "b", "c"] || side == vec!["d", "e", "f"]);
+
+        // every edge is directed, so cutting just a -> b works as well as the bridge
+        let flow = g.max_flow(index("a"), index("f"), |_| 1);
+        assert_eq!(flow.weight, 1);
+        assert!(flow.side.contains(&index("a")) && !flow.side.contains(&index("d")));
+        assert_eq!(g.max_flow(index("f"), index("a"), |_| 1).weight, 0);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let g = graph(&[("a", "b")]);
+        assert_eq!(
+            g.to_dot(|node| node.to_string(), |_| None),
+            "digraph {\n    0 [label=\"a\"];\n    1 [label=\"b\"];\n    0 -> 1;\n}\n"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use itertools::Itertools;
 
+use super::{shortest_path, GridBounds, GridLike};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Grid<T> {
     top_left: GridPoint,
@@ -152,10 +154,6 @@ where
     ///
     /// This still disallows setting negative coordinates
     pub fn set_expand(&mut self, point: &GridPoint, value: T) -> Result<(), String> {
-        // println!(
-        //     "set_expand({}, _), top_left = {}, dimensions = ({}, {})",
-        //     point, self.top_left, self.width, self.height
-        // );
         if point.x >= self.top_left.x + self.width as i64 {
             let amount = (point.x - (self.top_left.x + self.width as i64) + 1) as usize;
             self.expand_right(amount);
@@ -328,36 +326,6 @@ where
         Ok(())
     }
 
-    pub fn flood<FV, FF>(
-        &mut self,
-        point: &GridPoint,
-        value: &FV,
-        is_floodable: &FF,
-    ) -> Result<(), String>
-    where
-        FV: Fn() -> T,
-        FF: Fn(&T) -> bool,
-    {
-        // check if this location even makes sense
-        if !self.is_within_bounds(point) {
-            return Ok(());
-        }
-
-        // check if we've already flooded this location
-        if !is_floodable(self.must_get(point)) {
-            return Ok(());
-        }
-
-        self.set(point, value())?;
-
-        // flood the rest
-        for neighbor in point.orthogonal_neighbors() {
-            self.flood(&neighbor, value, is_floodable)?;
-        }
-
-        Ok(())
-    }
-
     pub fn map_all<U>(&mut self, mut map_fn: impl FnMut(&T) -> U) -> Result<Grid<U>, String>
     where
         U: Default + std::fmt::Debug + Clone + PartialEq,
@@ -421,36 +389,11 @@ where
         })
     }
 
-    pub fn astar_distance_othogonal(
-        &self,
-        start: &GridPoint,
-        end: &GridPoint,
-        traversable: impl Fn(&T) -> bool,
-    ) -> Option<u64> {
-        let successors = |point: &GridPoint| {
-            point
-                .orthogonal_neighbors()
-                .into_iter()
-                .filter_map(|neighbor| {
-                    // if we're off the map or not traversable, we can't move there
-                    if !self.is_within_bounds(&neighbor) || !traversable(self.must_get(&neighbor)) {
-                        return None;
-                    }
-                    Some((neighbor, 1))
-                })
-        };
-        let heuristic = |point: &GridPoint| start.manhattan_distance_to(point);
-        let success = |point: &GridPoint| point == end;
-
-        pathfinding::directed::astar::astar(start, successors, heuristic, success)
-            .map(|(_, distance)| distance)
-    }
-
     /// Compute the shortest distance between two points using A*
     ///
-    /// This assumes that the cache was used for previous calls to `astar_distance_othogonal_with_cache`
+    /// This assumes that the cache was used for previous calls to `astar_distance_orthogonal_with_cache`
     /// using the same parameters except the starting point
-    pub fn astar_distance_othogonal_with_cache(
+    pub fn astar_distance_orthogonal_with_cache(
         &self,
         start: &GridPoint,
         end: &GridPoint,
@@ -491,6 +434,191 @@ where
             None => None,
         }
     }
+
+    /// Find the cheapest path between two points when movement comes in straight runs
+    ///
+    /// Every run must be between `rules.min_straight` and `rules.max_straight` steps long,
+    /// after which the path has to turn (the path also has to end on a complete run).
+    /// `cost_fn` gives the cost of stepping onto a location, or `None` if it can't be entered
+    pub fn constrained_shortest_path(
+        &self,
+        start: &GridPoint,
+        end: &GridPoint,
+        cost_fn: impl Fn(&T) -> Option<u64>,
+        rules: MovementRules,
+    ) -> Option<GridPath> {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        struct State {
+            location: GridPoint,
+            direction_to_here: Option<CardinalDirection>,
+        }
+
+        let successors = |state: &State| {
+            // valid cardinal directions we can travel in from here
+            let valid_directions = match state.direction_to_here {
+                Some(direction) if rules.allow_reverse => vec![
+                    direction.turn_left(),
+                    direction.turn_right(),
+                    direction.opposite(),
+                ],
+                Some(direction) => vec![direction.turn_left(), direction.turn_right()],
+                None => CardinalDirection::all().to_vec(),
+            };
+            let turn_cost = match state.direction_to_here {
+                Some(_) => rules.turn_cost,
+                None => 0,
+            };
+
+            let mut successors = Vec::new();
+            for direction in valid_directions {
+                // accumulate the cost of each location we traverse on the way there
+                let mut cost = turn_cost;
+                let mut location = state.location;
+                for distance in 1..=rules.max_straight {
+                    location = location.neighbor_in_direction(direction);
+                    let Some(step_cost) = self.get(&location).and_then(&cost_fn) else {
+                        break;
+                    };
+                    cost += step_cost;
+
+                    if distance >= rules.min_straight {
+                        let next = State {
+                            location,
+                            direction_to_here: Some(direction),
+                        };
+                        successors.push((next, cost));
+                    }
+                }
+            }
+            successors
+        };
+
+        // the cheapest possible step keeps the heuristic from ever overestimating
+        let min_step_cost = self
+            .entries()
+            .filter_map(|entry| cost_fn(entry.value))
+            .min()
+            .unwrap_or(0);
+        let heuristic = |state: &State| state.location.manhattan_distance_to(end) * min_step_cost;
+
+        let start_state = State {
+            location: *start,
+            direction_to_here: None,
+        };
+        let optimal = shortest_path(
+            [start_state],
+            successors,
+            |state| state.location == *end,
+            heuristic,
+        )?;
+
+        let mut points = vec![*start];
+        for window in optimal.path.windows(2) {
+            points.extend(
+                window[0]
+                    .location
+                    .points_between_orthogonal_exclusive(&window[1].location),
+            );
+            points.push(window[1].location);
+        }
+
+        Some(GridPath {
+            points,
+            cost: optimal.cost,
+        })
+    }
+}
+
+/// Restrictions on how far a path may travel in a straight line before it must turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MovementRules {
+    /// The fewest steps that must be taken in a direction before turning or stopping
+    pub min_straight: usize,
+    /// The most steps that may be taken in a direction before having to turn
+    pub max_straight: usize,
+    /// Whether turning around counts as a turn
+    pub allow_reverse: bool,
+    /// An extra cost added every time the path turns
+    pub turn_cost: u64,
+}
+
+impl Default for MovementRules {
+    /// Unrestricted orthogonal movement
+    fn default() -> Self {
+        Self {
+            min_straight: 1,
+            max_straight: usize::MAX,
+            allow_reverse: true,
+            turn_cost: 0,
+        }
+    }
+}
+
+/// A path through a grid, visiting every point along it one step at a time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridPath {
+    pub points: Vec<GridPoint>,
+    pub cost: u64,
+}
+
+impl<T> GridLike<T> for Grid<T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    fn get(&self, point: &GridPoint) -> Option<&T> {
+        Grid::get(self, point)
+    }
+
+    fn set(&mut self, point: &GridPoint, value: T) -> Result<(), String> {
+        Grid::set(self, point, value)
+    }
+
+    fn bounds(&self) -> Option<GridBounds> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        Some(GridBounds::new(
+            self.top_left,
+            GridPoint {
+                x: self.top_left.x + self.width as i64 - 1,
+                y: self.top_left.y + self.height as i64 - 1,
+            },
+        ))
+    }
+
+    fn entries<'a>(&'a self) -> impl Iterator<Item = GridEntry<&'a T>>
+    where
+        T: 'a,
+    {
+        Grid::entries(self)
+    }
+
+    fn is_within_bounds(&self, point: &GridPoint) -> bool {
+        Grid::is_within_bounds(self, point)
+    }
+}
+
+/// Compute the shortest orthogonal distance between two points using A*
+///
+/// `can_enter` decides whether a point may be stepped onto, which lets bounded and
+/// unbounded grids share the same search
+pub(super) fn astar_distance_orthogonal_by(
+    start: &GridPoint,
+    end: &GridPoint,
+    can_enter: impl Fn(&GridPoint) -> bool,
+) -> Option<u64> {
+    let successors = |point: &GridPoint| {
+        point
+            .orthogonal_neighbors()
+            .into_iter()
+            .filter(|neighbor| can_enter(neighbor))
+            .map(|neighbor| (neighbor, 1))
+    };
+    let heuristic = |point: &GridPoint| point.manhattan_distance_to(end);
+    let success = |point: &GridPoint| point == end;
+
+    pathfinding::directed::astar::astar(start, successors, heuristic, success)
+        .map(|(_, distance)| distance)
 }
 
 impl<T> std::fmt::Display for Grid<T>
@@ -866,3 +994,113 @@ impl std::fmt::Display for Winding {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Grid<u64> {
+        let data = input
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| c.to_digit(10).unwrap() as u64)
+                    .collect()
+            })
+            .collect();
+        Grid::from_data(data).unwrap()
+    }
+
+    fn cheapest(grid: &Grid<u64>, end: (i64, i64), rules: MovementRules) -> Option<u64> {
+        grid.constrained_shortest_path(&(0, 0).into(), &end.into(), |&cost| Some(cost), rules)
+            .map(|path| path.cost)
+    }
+
+    fn straight_runs(min_straight: usize, max_straight: usize) -> MovementRules {
+        MovementRules {
+            min_straight,
+            max_straight,
+            allow_reverse: false,
+            turn_cost: 0,
+        }
+    }
+
+    #[test]
+    fn test_constrained_shortest_path_crucibles() {
+        let grid = digits(
+            "\
+2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533",
+        );
+        let end = (12, 12);
+        assert_eq!(cheapest(&grid, end, straight_runs(1, 3)), Some(102));
+        assert_eq!(cheapest(&grid, end, straight_runs(4, 10)), Some(94));
+
+        // an ultra crucible can't stop at the end after only one step, so has to go the long way
+        let grid = digits("111111111111\n999999999991\n999999999991\n999999999991\n999999999991");
+        assert_eq!(cheapest(&grid, (11, 4), straight_runs(4, 10)), Some(71));
+    }
+
+    #[test]
+    fn test_constrained_shortest_path_path_points() {
+        let grid = digits("123\n456");
+        let path = grid
+            .constrained_shortest_path(
+                &(0, 0).into(),
+                &(2, 1).into(),
+                |&cost| Some(cost),
+                MovementRules::default(),
+            )
+            .unwrap();
+
+        assert_eq!(path.cost, 2 + 3 + 6);
+        let points: Vec<GridPoint> = [(0, 0), (1, 0), (2, 0), (2, 1)].map(GridPoint::from).into();
+        assert_eq!(path.points, points);
+    }
+
+    #[test]
+    fn test_constrained_shortest_path_turn_cost() {
+        // going around the expensive cells takes two turns, while going straight through takes none
+        let grid = digits("19991\n11111");
+        let with_turn_cost = |turn_cost| MovementRules {
+            turn_cost,
+            ..MovementRules::default()
+        };
+
+        assert_eq!(cheapest(&grid, (4, 0), with_turn_cost(0)), Some(6));
+        assert_eq!(cheapest(&grid, (4, 0), with_turn_cost(5)), Some(16));
+        assert_eq!(cheapest(&grid, (4, 0), with_turn_cost(20)), Some(28));
+    }
+
+    #[test]
+    fn test_constrained_shortest_path_reverse() {
+        // in a corridor, the only way to end up one step back west with runs of at least two is
+        // to go east first and then turn around
+        let grid = digits("11111");
+        let rules = |allow_reverse, turn_cost| MovementRules {
+            min_straight: 2,
+            max_straight: usize::MAX,
+            allow_reverse,
+            turn_cost,
+        };
+        let cheapest = |rules| {
+            grid.constrained_shortest_path(&(2, 0).into(), &(1, 0).into(), |&c| Some(c), rules)
+                .map(|path| path.cost)
+        };
+
+        assert_eq!(cheapest(rules(false, 0)), None);
+        assert_eq!(cheapest(rules(true, 0)), Some(5));
+        assert_eq!(cheapest(rules(true, 10)), Some(15));
+    }
+}
@@ -0,0 +1,186 @@
+use itertools::Itertools;
+
+use super::{astar_distance_orthogonal_by, GridEntry, GridPoint};
+
+/// The rectangular region of points that a grid covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridBounds {
+    /// The top-left corner, inclusive
+    pub top_left: GridPoint,
+    /// The bottom-right corner, inclusive
+    pub bottom_right: GridPoint,
+}
+
+impl GridBounds {
+    pub fn new(top_left: GridPoint, bottom_right: GridPoint) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    /// A single-point region
+    pub fn around(point: GridPoint) -> Self {
+        Self::new(point, point)
+    }
+
+    pub fn width(&self) -> usize {
+        (self.bottom_right.x - self.top_left.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.bottom_right.y - self.top_left.y + 1) as usize
+    }
+
+    pub fn contains(&self, point: &GridPoint) -> bool {
+        point.x >= self.top_left.x
+            && point.x <= self.bottom_right.x
+            && point.y >= self.top_left.y
+            && point.y <= self.bottom_right.y
+    }
+
+    /// Grows the region just enough to also cover `point`
+    pub fn expand_to(&mut self, point: &GridPoint) {
+        self.top_left.x = self.top_left.x.min(point.x);
+        self.top_left.y = self.top_left.y.min(point.y);
+        self.bottom_right.x = self.bottom_right.x.max(point.x);
+        self.bottom_right.y = self.bottom_right.y.max(point.y);
+    }
+
+    /// All points in the region, row by row
+    pub fn points(&self) -> impl Iterator<Item = GridPoint> {
+        let Self {
+            top_left,
+            bottom_right,
+        } = *self;
+        (top_left.y..=bottom_right.y)
+            .flat_map(move |y| (top_left.x..=bottom_right.x).map(move |x| GridPoint { x, y }))
+    }
+}
+
+/// Common behaviour of grids, no matter how they store their data
+///
+/// This is what lets algorithms like flood filling, rendering and pathfinding work on
+/// a dense [`Grid`](super::Grid) and a [`SparseGrid`](super::SparseGrid) alike
+pub trait GridLike<T> {
+    fn get(&self, point: &GridPoint) -> Option<&T>;
+
+    fn set(&mut self, point: &GridPoint, value: T) -> Result<(), String>;
+
+    /// The region covered by the grid, or `None` if the grid covers nothing at all
+    fn bounds(&self) -> Option<GridBounds>;
+
+    /// The stored entries of the grid
+    ///
+    /// For dense grids this is every point within bounds, but sparse grids only yield
+    /// points which have actually been set
+    fn entries<'a>(&'a self) -> impl Iterator<Item = GridEntry<&'a T>>
+    where
+        T: 'a;
+
+    fn is_within_bounds(&self, point: &GridPoint) -> bool {
+        self.bounds().is_some_and(|bounds| bounds.contains(point))
+    }
+
+    /// Flood the region containing `point`, setting everything floodable to `value()`
+    fn flood<FV, FF>(
+        &mut self,
+        point: &GridPoint,
+        value: &FV,
+        is_floodable: &FF,
+    ) -> Result<(), String>
+    where
+        FV: Fn() -> T,
+        FF: Fn(&T) -> bool,
+    {
+        let mut to_flood = vec![*point];
+        while let Some(point) = to_flood.pop() {
+            // check if this location even makes sense, and if we've already flooded it
+            if !self.get(&point).is_some_and(is_floodable) {
+                continue;
+            }
+
+            self.set(&point, value())?;
+
+            // flood the rest
+            to_flood.extend(point.orthogonal_neighbors());
+        }
+
+        Ok(())
+    }
+
+    /// Draw every point within bounds, one line per row
+    fn render(&self, render_fn: impl Fn(&T) -> char) -> String {
+        let Some(bounds) = self.bounds() else {
+            return String::new();
+        };
+
+        (bounds.top_left.y..=bounds.bottom_right.y)
+            .map(|y| {
+                (bounds.top_left.x..=bounds.bottom_right.x)
+                    .map(|x| self.get(&GridPoint { x, y }).map_or(' ', &render_fn))
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    fn astar_distance_orthogonal(
+        &self,
+        start: &GridPoint,
+        end: &GridPoint,
+        traversable: impl Fn(&T) -> bool,
+    ) -> Option<u64> {
+        astar_distance_orthogonal_by(start, end, |point| {
+            // if we're off the map or not traversable, we can't move there
+            self.get(point).is_some_and(&traversable)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{Grid, SparseGrid};
+
+    /// A ring of walls with a gap in its right side, so that flooding from inside leaks out
+    const WALLS: [(i64, i64); 7] = [(0, 0), (1, 0), (2, 0), (0, 1), (0, 2), (1, 2), (2, 2)];
+
+    fn flood_inside<G: GridLike<char>>(grid: &mut G) {
+        grid.flood(&(1, 1).into(), &|| '~', &|c| *c == '.').unwrap();
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut bounds = GridBounds::around((1, 1).into());
+        bounds.expand_to(&(-1, 2).into());
+        assert_eq!(bounds, GridBounds::new((-1, 1).into(), (1, 2).into()));
+        assert_eq!((bounds.width(), bounds.height()), (3, 2));
+        assert!(bounds.contains(&(0, 2).into()));
+        assert!(!bounds.contains(&(0, 3).into()));
+        assert_eq!(bounds.points().count(), 6);
+    }
+
+    #[test]
+    fn test_flood_dense_grid() {
+        let mut grid = Grid::from_data(vec![vec!['.'; 4]; 4]).unwrap();
+        for wall in WALLS {
+            grid.set(&wall.into(), '#').unwrap();
+        }
+
+        flood_inside(&mut grid);
+        assert_eq!(grid.render(|c| *c), "###~\n#~~~\n###~\n~~~~");
+    }
+
+    #[test]
+    fn test_flood_sparse_grid() {
+        // only points within the bounds of what's been set can be flooded
+        let mut grid = SparseGrid::with_fill('.');
+        for wall in WALLS {
+            grid.set(&wall.into(), '#').unwrap();
+        }
+        grid.set(&(3, 3).into(), '.').unwrap();
+
+        flood_inside(&mut grid);
+        assert_eq!(grid.render(|c| *c), "###~\n#~~~\n###~\n~~~~");
+    }
+}
@@ -1,35 +1,126 @@
 use std::{fmt, ops};
 
+use super::IntervalIndex;
+
+/// The integer types that intervals can be built from
+pub trait IntervalBound: num::PrimInt + fmt::Debug {}
+
+impl<N> IntervalBound for N where N: num::PrimInt + fmt::Debug {}
+
+/// A set of integers, stored as a list of intervals
+///
+/// The intervals are always kept normalized:
+/// - sorted by their start
+/// - non-empty
+/// - disjoint (no two intervals overlap)
+/// - coalesced (two touching intervals only stay separate if they carry different data)
+///
+/// Each interval may carry some data along with it. An intersection keeps the data of both
+/// sides, so payloads like offsets can be composed. A union is a set union: where both sides
+/// overlap it keeps the data of `self` and adds any payloads of `other` that aren't already
+/// there, so `a | a == a` and equal payloads coalesce
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct SortedDisjointIntervalList<D>
+pub struct SortedDisjointIntervalList<D, N = i64>
 where
-    D: Clone,
+    D: Clone + PartialEq,
+    N: IntervalBound,
 {
-    pub intervals: Vec<Interval<D>>,
+    intervals: Vec<Interval<D, N>>,
 }
 
-impl<D> SortedDisjointIntervalList<D>
+impl<D, N> SortedDisjointIntervalList<D, N>
 where
-    D: Clone,
+    D: Clone + PartialEq,
+    N: IntervalBound,
 {
-    pub fn new(mut intervals: Vec<Interval<D>>) -> Self {
-        intervals.sort_by(|a, b| a.range.start.cmp(&b.range.start));
-        Self { intervals }
+    /// Build a list out of any intervals, normalizing them as needed
+    ///
+    /// Where intervals overlap, the overlapping part carries the data of all of them
+    pub fn new(intervals: Vec<Interval<D, N>>) -> Self {
+        Self {
+            intervals: normalize(intervals),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    pub fn intervals(&self) -> &[Interval<D, N>] {
+        &self.intervals
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<D, N>> {
+        self.intervals.iter()
     }
 
     pub fn num_ranges(&self) -> usize {
         self.intervals.len()
     }
 
-    pub fn span(&self) -> u64 {
-        let mut span = 0;
-        for interval in &self.intervals {
-            span += interval.range.size_hint().0;
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of integers in the list
+    ///
+    /// Disjoint intervals of even the widest integer type can't hold more than `u128::MAX`
+    /// values between them, so this is always exact
+    pub fn span(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// The interval containing `point`, if there is one
+    pub fn interval_containing(&self, point: N) -> Option<&Interval<D, N>> {
+        // the intervals are sorted and disjoint, so their ends are sorted too
+        let i = self
+            .intervals
+            .partition_point(|interval| interval.range.end <= point);
+        self.intervals
+            .get(i)
+            .filter(|interval| interval.contains(point))
+    }
+
+    pub fn contains(&self, point: N) -> bool {
+        self.interval_containing(point).is_some()
+    }
+
+    /// Every interval sharing at least one point with `range`
+    pub fn overlapping(&self, range: &ops::Range<N>) -> &[Interval<D, N>] {
+        if range.is_empty() {
+            return &[];
+        }
+        let first = self
+            .intervals
+            .partition_point(|interval| interval.range.end <= range.start);
+        let last = self
+            .intervals
+            .partition_point(|interval| interval.range.start < range.end);
+        &self.intervals[first..last.max(first)]
+    }
+
+    /// Whether any point is in both lists
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let mut ai: usize = 0;
+        let mut bi: usize = 0;
+        while ai < self.intervals.len() && bi < other.intervals.len() {
+            let a = &self.intervals[ai];
+            let b = &other.intervals[bi];
+
+            if a.overlaps(b) {
+                return true;
+            }
+
+            if a.range.end < b.range.end {
+                ai += 1;
+            } else {
+                bi += 1;
+            }
         }
-        span as u64
+        false
     }
 
-    pub fn intersect(&self, other: &Self) -> SortedDisjointIntervalList<D> {
+    pub fn intersect(&self, other: &Self) -> SortedDisjointIntervalList<D, N> {
         let mut ai: usize = 0;
         let mut bi: usize = 0;
 
@@ -52,51 +143,175 @@ where
         SortedDisjointIntervalList::new(intersections)
     }
 
-    pub fn subtract(&self, other: &Self) -> SortedDisjointIntervalList<D> {
+    /// Everything in `self` which isn't in `other`, keeping the data of `self`
+    pub fn subtract(&self, other: &Self) -> SortedDisjointIntervalList<D, N> {
         let mut subtractions = Vec::new();
+
+        // both lists are sorted, so we never need to look back at an interval of `other`
+        // which ended before the current interval of `self` started
+        let mut first_relevant = 0;
         for a in &self.intervals {
-            let mut subtraction: Vec<Interval<D>> = vec![a.clone()];
-            for b in &other.intervals {
-                if let Some(a_comp) = subtraction.last() {
-                    subtraction = a_comp.subtract(b)
+            while other
+                .intervals
+                .get(first_relevant)
+                .is_some_and(|b| b.range.end <= a.range.start)
+            {
+                first_relevant += 1;
+            }
+
+            let mut remaining_start = a.range.start;
+            for b in other.intervals[first_relevant..]
+                .iter()
+                .take_while(|b| b.range.start < a.range.end)
+            {
+                if b.range.start > remaining_start {
+                    subtractions.push(Interval {
+                        range: remaining_start..b.range.start,
+                        data: a.data.clone(),
+                    });
                 }
+                remaining_start = remaining_start.max(b.range.end);
+            }
+
+            if remaining_start < a.range.end {
+                subtractions.push(Interval {
+                    range: remaining_start..a.range.end,
+                    data: a.data.clone(),
+                });
             }
-            subtractions.extend(subtraction);
         }
+
         SortedDisjointIntervalList::new(subtractions)
     }
 
-    pub fn merge(&self, other: &Self) -> SortedDisjointIntervalList<D> {
-        let projection_intersection = self.intersect(other);
-        let sub_self = self.subtract(&projection_intersection);
-        let sub_other = other.subtract(&projection_intersection);
+    /// Everything in either list. Where both lists overlap, the data of `self` is kept along
+    /// with any data of `other` which `self` doesn't already have
+    pub fn union(&self, other: &Self) -> SortedDisjointIntervalList<D, N> {
+        let mut pieces = self.subtract(other).intervals;
+        pieces.extend(other.subtract(self).intervals);
 
-        let res = SortedDisjointIntervalList::new(
-            sub_self
-                .intervals
-                .iter()
-                .chain(sub_other.intervals.iter())
-                .chain(projection_intersection.intervals.iter())
-                .cloned()
-                .collect(),
-        );
+        for a in &self.intervals {
+            for b in other.overlapping(&a.range) {
+                let mut data = a.data.clone();
+                data.extend(b.data.iter().filter(|d| !a.data.contains(d)).cloned());
+                pieces.push(Interval {
+                    range: a.range.start.max(b.range.start)..a.range.end.min(b.range.end),
+                    data,
+                });
+            }
+        }
+
+        // the pieces are already disjoint, so this only sorts and coalesces them
+        SortedDisjointIntervalList::new(pieces)
+    }
+
+    /// Everything in exactly one of the two lists
+    pub fn symmetric_difference(&self, other: &Self) -> SortedDisjointIntervalList<D, N> {
+        self.subtract(other).union(&other.subtract(self))
+    }
 
-        res
+    /// Everything within `universe` which isn't in this list
+    pub fn complement(&self, universe: ops::Range<N>) -> SortedDisjointIntervalList<D, N> {
+        SortedDisjointIntervalList::new(vec![Interval::new(universe)]).subtract(self)
     }
 }
 
-impl<D> From<Vec<Interval<D>>> for SortedDisjointIntervalList<D>
+impl<D, N> Default for SortedDisjointIntervalList<D, N>
 where
-    D: Clone,
+    D: Clone + PartialEq,
+    N: IntervalBound,
+{
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<D, N> From<Vec<Interval<D, N>>> for SortedDisjointIntervalList<D, N>
+where
+    D: Clone + PartialEq,
+    N: IntervalBound,
 {
-    fn from(intervals: Vec<Interval<D>>) -> Self {
+    fn from(intervals: Vec<Interval<D, N>>) -> Self {
         SortedDisjointIntervalList::new(intervals)
     }
 }
 
-impl<D> fmt::Display for SortedDisjointIntervalList<D>
+impl<D, N> FromIterator<Interval<D, N>> for SortedDisjointIntervalList<D, N>
 where
-    D: Clone + fmt::Display,
+    D: Clone + PartialEq,
+    N: IntervalBound,
+{
+    fn from_iter<I: IntoIterator<Item = Interval<D, N>>>(iter: I) -> Self {
+        SortedDisjointIntervalList::new(iter.into_iter().collect())
+    }
+}
+
+/// Implements a binary operator for every combination of owned and borrowed lists
+macro_rules! impl_interval_list_op {
+    ($op_trait:ident, $op_fn:ident, $method:ident) => {
+        impl<D, N> ops::$op_trait<&SortedDisjointIntervalList<D, N>>
+            for &SortedDisjointIntervalList<D, N>
+        where
+            D: Clone + PartialEq,
+            N: IntervalBound,
+        {
+            type Output = SortedDisjointIntervalList<D, N>;
+
+            fn $op_fn(self, other: &SortedDisjointIntervalList<D, N>) -> Self::Output {
+                self.$method(other)
+            }
+        }
+
+        impl<D, N> ops::$op_trait<SortedDisjointIntervalList<D, N>>
+            for &SortedDisjointIntervalList<D, N>
+        where
+            D: Clone + PartialEq,
+            N: IntervalBound,
+        {
+            type Output = SortedDisjointIntervalList<D, N>;
+
+            fn $op_fn(self, other: SortedDisjointIntervalList<D, N>) -> Self::Output {
+                self.$method(&other)
+            }
+        }
+
+        impl<D, N> ops::$op_trait<&SortedDisjointIntervalList<D, N>>
+            for SortedDisjointIntervalList<D, N>
+        where
+            D: Clone + PartialEq,
+            N: IntervalBound,
+        {
+            type Output = SortedDisjointIntervalList<D, N>;
+
+            fn $op_fn(self, other: &SortedDisjointIntervalList<D, N>) -> Self::Output {
+                self.$method(other)
+            }
+        }
+
+        impl<D, N> ops::$op_trait<SortedDisjointIntervalList<D, N>>
+            for SortedDisjointIntervalList<D, N>
+        where
+            D: Clone + PartialEq,
+            N: IntervalBound,
+        {
+            type Output = SortedDisjointIntervalList<D, N>;
+
+            fn $op_fn(self, other: SortedDisjointIntervalList<D, N>) -> Self::Output {
+                self.$method(&other)
+            }
+        }
+    };
+}
+
+impl_interval_list_op!(BitAnd, bitand, intersect);
+impl_interval_list_op!(BitOr, bitor, union);
+impl_interval_list_op!(Sub, sub, subtract);
+impl_interval_list_op!(BitXor, bitxor, symmetric_difference);
+
+impl<D, N> fmt::Display for SortedDisjointIntervalList<D, N>
+where
+    D: Clone + PartialEq + fmt::Display,
+    N: IntervalBound,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let range_strs = self.intervals.iter().map(|range| range.to_string());
@@ -105,36 +320,181 @@ where
     }
 }
 
-pub type BlankInterval = Interval<()>;
+/// Sort, split and coalesce arbitrary intervals into a normalized list
+fn normalize<D, N>(mut intervals: Vec<Interval<D, N>>) -> Vec<Interval<D, N>>
+where
+    D: Clone + PartialEq,
+    N: IntervalBound,
+{
+    intervals.retain(|interval| !interval.is_empty());
+    // a stable sort keeps the data of overlapping intervals in the order they were given
+    intervals.sort_by_key(|interval| interval.range.start);
+
+    let already_disjoint = intervals
+        .windows(2)
+        .all(|pair| pair[0].range.end <= pair[1].range.start);
+
+    let disjoint = if already_disjoint {
+        intervals
+    } else {
+        // cut the number line at every endpoint, and give each piece the data of
+        // every interval covering it
+        let mut boundaries = intervals
+            .iter()
+            .flat_map(|interval| [interval.range.start, interval.range.end])
+            .collect::<Vec<_>>();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let mut pieces = Vec::new();
+        let mut active: Vec<&Interval<D, N>> = Vec::new();
+        let mut next = 0;
+        for bounds in boundaries.windows(2) {
+            let (start, end) = (bounds[0], bounds[1]);
+
+            active.retain(|interval| interval.range.end > start);
+            while next < intervals.len() && intervals[next].range.start <= start {
+                active.push(&intervals[next]);
+                next += 1;
+            }
+
+            if !active.is_empty() {
+                pieces.push(Interval {
+                    range: start..end,
+                    data: active
+                        .iter()
+                        .flat_map(|interval| interval.data.iter().cloned())
+                        .collect(),
+                });
+            }
+        }
+        pieces
+    };
+
+    let mut coalesced: Vec<Interval<D, N>> = Vec::with_capacity(disjoint.len());
+    for interval in disjoint {
+        match coalesced.last_mut() {
+            Some(last) if last.range.end == interval.range.start && last.data == interval.data => {
+                last.range.end = interval.range.end;
+            }
+            _ => coalesced.push(interval),
+        }
+    }
+    coalesced
+}
+
+pub type BlankInterval<N = i64> = Interval<(), N>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Interval<D>
+pub struct Interval<D, N = i64>
 where
     D: Clone,
+    N: IntervalBound,
 {
-    range: ops::Range<i64>,
+    range: ops::Range<N>,
     data: Vec<D>,
 }
 
-impl<D> Interval<D>
+impl<D, N> Interval<D, N>
 where
     D: Clone,
+    N: IntervalBound,
 {
-    pub fn new(range: ops::Range<i64>) -> Self {
+    pub fn new(range: ops::Range<N>) -> Self {
         Self {
             range,
             data: vec![],
         }
     }
 
-    pub fn new_with_data(range: ops::Range<i64>, data: D) -> Self {
+    pub fn new_with_data(range: ops::Range<N>, data: D) -> Self {
         Self {
             range,
             data: vec![data],
         }
     }
 
-    fn intersect(&self, other: &Interval<D>) -> Option<Interval<D>> {
+    pub fn range(&self) -> &ops::Range<N> {
+        &self.range
+    }
+
+    pub fn start(&self) -> N {
+        self.range.start
+    }
+
+    pub fn end(&self) -> N {
+        self.range.end
+    }
+
+    pub fn data(&self) -> &[D] {
+        &self.data
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    /// The number of integers in the interval
+    ///
+    /// This is exact even when the interval covers most of a wide signed type, like `i128::MIN..i128::MAX`
+    pub fn len(&self) -> u128 {
+        if self.is_empty() {
+            return 0;
+        }
+        match self.range.end.checked_sub(&self.range.start) {
+            Some(length) => length
+                .to_u128()
+                .expect("the difference of two ordered integers is never negative"),
+            // only a signed type can overflow here, and every signed type fits in an i128,
+            // whose widest difference still fits in a u128
+            None => {
+                let start = self
+                    .range
+                    .start
+                    .to_i128()
+                    .expect("signed types fit in i128");
+                let end = self.range.end.to_i128().expect("signed types fit in i128");
+                end.wrapping_sub(start) as u128
+            }
+        }
+    }
+
+    pub fn contains(&self, point: N) -> bool {
+        self.range.contains(&point)
+    }
+
+    pub fn overlaps(&self, other: &Interval<D, N>) -> bool {
+        self.range.start.max(other.range.start) < self.range.end.min(other.range.end)
+    }
+
+    /// The same interval, moved along the number line by `amount`
+    pub fn shifted_by(&self, amount: N) -> Interval<D, N> {
+        Self {
+            range: self.range.start + amount..self.range.end + amount,
+            data: self.data.clone(),
+        }
+    }
+
+    /// A different range, carrying the same data
+    pub fn with_range(&self, range: ops::Range<N>) -> Interval<D, N> {
+        Self {
+            range,
+            data: self.data.clone(),
+        }
+    }
+
+    /// The same interval, carrying different data
+    pub fn with_data<E>(&self, data: Vec<E>) -> Interval<E, N>
+    where
+        E: Clone,
+    {
+        Interval {
+            range: self.range.clone(),
+            data,
+        }
+    }
+
+    pub fn intersect(&self, other: &Interval<D, N>) -> Option<Interval<D, N>> {
         let a = &self.range;
         let b = &other.range;
 
@@ -153,7 +513,7 @@ where
         }
     }
 
-    fn subtract(&self, other: &Interval<D>) -> Vec<Interval<D>> {
+    pub fn subtract(&self, other: &Interval<D, N>) -> Vec<Interval<D, N>> {
         match self.intersect(other) {
             // if there's some intersection, figure out which part to remove
             Some(intersection) => {
@@ -195,15 +555,447 @@ where
     }
 }
 
-// impl_op_ex!(&|a: &Interval<_>, b: &Interval| -> Option<Interval> { a.intersect(b) });
+impl<D, N> From<ops::Range<N>> for Interval<D, N>
+where
+    D: Clone,
+    N: IntervalBound,
+{
+    fn from(range: ops::Range<N>) -> Self {
+        Self::new(range)
+    }
+}
+
+impl<D, N> TryFrom<ops::RangeInclusive<N>> for Interval<D, N>
+where
+    D: Clone,
+    N: IntervalBound,
+{
+    type Error = String;
 
-// impl_op_ex!(-|a: &Interval, b: &Interval| -> Option<Vec<Interval>> { a.subtract(b) });
+    /// Intervals are half-open, so this fails only if the range ends at the largest value of its type
+    fn try_from(range: ops::RangeInclusive<N>) -> Result<Self, Self::Error> {
+        let (start, end) = range.into_inner();
+        if end < start {
+            return Ok(Self::new(start..start));
+        }
+        let end = end
+            .checked_add(&N::one())
+            .ok_or_else(|| format!("can't represent an interval ending at {:?}", end))?;
+        Ok(Self::new(start..end))
+    }
+}
 
-impl<D> fmt::Display for Interval<D>
+impl<D, N> fmt::Display for Interval<D, N>
 where
     D: Clone + fmt::Display,
+    N: IntervalBound,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "({:?})", self.range)
     }
 }
+
+/// A piecewise function over the integers, which adds a fixed offset to every value in
+/// each of its ranges
+///
+/// Values outside of every range map to themselves
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeMap {
+    /// Normalized so that every piece carries exactly one non-zero offset
+    offsets: SortedDisjointIntervalList<i64>,
+}
+
+impl RangeMap {
+    /// The map which leaves every value where it is
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    /// Build a map from `(range, offset)` pairs
+    ///
+    /// The ranges may touch, but can't overlap
+    pub fn new(mappings: impl IntoIterator<Item = (ops::Range<i64>, i64)>) -> Result<Self, String> {
+        let mut mappings = mappings
+            .into_iter()
+            .filter(|(range, _)| !range.is_empty())
+            .collect::<Vec<_>>();
+        mappings.sort_by_key(|(range, _)| range.start);
+
+        if let Some(pair) = mappings
+            .windows(2)
+            .find(|pair| pair[0].0.end > pair[1].0.start)
+        {
+            return Err(format!(
+                "can't map overlapping ranges {:?} and {:?}",
+                pair[0].0, pair[1].0
+            ));
+        }
+
+        Ok(Self::from_disjoint(mappings))
+    }
+
+    /// Build a map which sends `source_start..source_start + length` onto
+    /// `destination_start..destination_start + length`, for every `(destination_start, source_start, length)`
+    pub fn from_destination_source_lengths(
+        mappings: impl IntoIterator<Item = (i64, i64, i64)>,
+    ) -> Result<Self, String> {
+        Self::new(
+            mappings
+                .into_iter()
+                .map(|(destination_start, source_start, length)| {
+                    (
+                        source_start..(source_start + length),
+                        destination_start - source_start,
+                    )
+                }),
+        )
+    }
+
+    fn from_disjoint(mappings: impl IntoIterator<Item = (ops::Range<i64>, i64)>) -> Self {
+        Self {
+            offsets: mappings
+                .into_iter()
+                .filter(|(_, offset)| *offset != 0)
+                .map(|(range, offset)| Interval::new_with_data(range, offset))
+                .collect(),
+        }
+    }
+
+    /// Every range which is moved by the map, along with how far it is moved
+    pub fn pieces(&self) -> impl Iterator<Item = (&ops::Range<i64>, i64)> {
+        self.offsets
+            .iter()
+            .map(|interval| (interval.range(), interval.data[0]))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    pub fn offset_at(&self, value: i64) -> i64 {
+        self.offsets
+            .interval_containing(value)
+            .map_or(0, |interval| interval.data[0])
+    }
+
+    pub fn get(&self, value: i64) -> i64 {
+        value + self.offset_at(value)
+    }
+
+    /// Cut `interval` up wherever the offset changes, giving each piece along with its offset
+    fn split<D>(&self, interval: &Interval<D>) -> Vec<(Interval<D>, i64)>
+    where
+        D: Clone + PartialEq,
+    {
+        let mut pieces = Vec::new();
+        let mut unmoved_start = interval.range.start;
+        for moved in self.offsets.overlapping(&interval.range) {
+            let start = moved.range.start.max(interval.range.start);
+            let end = moved.range.end.min(interval.range.end);
+            if start > unmoved_start {
+                pieces.push((interval.with_range(unmoved_start..start), 0));
+            }
+            pieces.push((interval.with_range(start..end), moved.data[0]));
+            unmoved_start = end;
+        }
+        if unmoved_start < interval.range.end {
+            pieces.push((interval.with_range(unmoved_start..interval.range.end), 0));
+        }
+        pieces
+    }
+
+    /// Every value which is moved by the map
+    fn domain<D>(&self) -> SortedDisjointIntervalList<D>
+    where
+        D: Clone + PartialEq,
+    {
+        self.offsets
+            .iter()
+            .map(|interval| Interval::new(interval.range.clone()))
+            .collect()
+    }
+
+    /// The image of every value in `list`, keeping the data of each interval
+    pub fn apply<D>(&self, list: &SortedDisjointIntervalList<D>) -> SortedDisjointIntervalList<D>
+    where
+        D: Clone + PartialEq,
+    {
+        list.iter()
+            .flat_map(|interval| self.split(interval))
+            .map(|(piece, offset)| piece.shifted_by(offset))
+            .collect()
+    }
+
+    /// Every value which this map sends somewhere in `list`, keeping the data of each interval
+    pub fn preimage<D>(&self, list: &SortedDisjointIntervalList<D>) -> SortedDisjointIntervalList<D>
+    where
+        D: Clone + PartialEq,
+    {
+        // the images of different ranges may overlap, so they need an index rather than a sorted list
+        let images = self
+            .pieces()
+            .map(|(range, offset)| {
+                Interval::new_with_data((range.start + offset)..(range.end + offset), offset)
+            })
+            .collect::<IntervalIndex<i64>>();
+
+        let mut preimage = (list - &self.domain()).intervals;
+        for interval in list.iter() {
+            for image in images.overlapping(&interval.range) {
+                let offset = image.data[0];
+                let start = image.range.start.max(interval.range.start);
+                let end = image.range.end.min(interval.range.end);
+                preimage.push(interval.with_range((start - offset)..(end - offset)));
+            }
+        }
+        SortedDisjointIntervalList::new(preimage)
+    }
+
+    /// The single map which does the same as applying `self`, and then `next`
+    pub fn then(&self, next: &RangeMap) -> RangeMap {
+        let mut composed = Vec::new();
+
+        for (range, offset) in self.pieces() {
+            let image = Interval::<()>::new((range.start + offset)..(range.end + offset));
+            for (piece, next_offset) in next.split(&image) {
+                composed.push((
+                    piece.range.start - offset..piece.range.end - offset,
+                    offset + next_offset,
+                ));
+            }
+        }
+
+        // anything left alone by `self` goes straight into `next`
+        for interval in (&next.offsets - &self.domain()).iter() {
+            composed.push((interval.range.clone(), interval.data[0]));
+        }
+
+        Self::from_disjoint(composed)
+    }
+
+    /// The map which undoes this one, if it is a bijection
+    pub fn inverse(&self) -> Result<RangeMap, String> {
+        let image = self
+            .pieces()
+            .map(|(range, offset)| Interval::new((range.start + offset)..(range.end + offset)))
+            .collect::<Vec<Interval<()>>>();
+        let total_length = image.iter().map(Interval::len).sum::<u128>();
+        let image = SortedDisjointIntervalList::new(image);
+
+        // the ranges must land exactly on top of each other, since everything else stays put
+        if image != self.domain() || image.span() != total_length {
+            return Err("range map isn't a bijection, so it can't be inverted".to_string());
+        }
+
+        Self::new(
+            self.pieces()
+                .map(|(range, offset)| ((range.start + offset)..(range.end + offset), -offset)),
+        )
+    }
+}
+
+impl fmt::Display for RangeMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let piece_strs = self
+            .pieces()
+            .map(|(range, offset)| format!("{:?} {:+}", range, offset));
+        write!(f, "[{}]", piece_strs.collect::<Vec<String>>().join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank(ranges: &[ops::Range<i64>]) -> SortedDisjointIntervalList<()> {
+        ranges.iter().cloned().map(BlankInterval::new).collect()
+    }
+
+    fn single(range: ops::Range<i64>) -> SortedDisjointIntervalList<()> {
+        SortedDisjointIntervalList::new(vec![BlankInterval::new(range)])
+    }
+
+    fn labelled(ranges: &[(ops::Range<i64>, char)]) -> SortedDisjointIntervalList<char> {
+        ranges
+            .iter()
+            .map(|(range, label)| Interval::new_with_data(range.clone(), *label))
+            .collect()
+    }
+
+    fn pieces<D: Clone + PartialEq>(
+        list: &SortedDisjointIntervalList<D>,
+    ) -> Vec<(ops::Range<i64>, Vec<D>)> {
+        list.iter()
+            .map(|interval| (interval.range().clone(), interval.data().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_union() {
+        let a = blank(&[0..3, 5..8]);
+        let b = blank(&[2..6, 10..12]);
+        assert_eq!(&a | &b, blank(&[0..8, 10..12]));
+        assert_eq!(a.union(&b), b.union(&a));
+        assert_eq!(&a | &SortedDisjointIntervalList::empty(), a);
+        assert_eq!(&a | &a, a);
+    }
+
+    #[test]
+    fn test_union_with_data() {
+        let a = labelled(&[(0..10, 'x')]);
+        assert_eq!(&a | &a, a);
+
+        let same = labelled(&[(5..15, 'x')]);
+        assert_eq!(pieces(&(&a | &same)), vec![(0..15, vec!['x'])]);
+
+        let different = labelled(&[(5..15, 'y')]);
+        assert_eq!(
+            pieces(&(&a | &different)),
+            vec![
+                (0..5, vec!['x']),
+                (5..10, vec!['x', 'y']),
+                (10..15, vec!['y'])
+            ]
+        );
+
+        // only payloads `self` doesn't have yet are added
+        let both = &a | &different;
+        assert_eq!(&both | &different, both);
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let a = blank(&[0..10, 20..30]);
+        let b = single(5..25);
+        assert_eq!(&a ^ &b, blank(&[0..5, 10..20, 25..30]));
+        assert_eq!(&a ^ &a, SortedDisjointIntervalList::empty());
+
+        let x = labelled(&[(0..10, 'x')]);
+        let y = labelled(&[(5..15, 'y')]);
+        assert_eq!(
+            pieces(&(x ^ y)),
+            vec![(0..5, vec!['x']), (10..15, vec!['y'])]
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        let list = blank(&[2..4, 6..8]);
+        assert_eq!(list.complement(0..10), blank(&[0..2, 4..6, 8..10]));
+        assert_eq!(list.complement(3..7), single(4..6));
+        assert_eq!(list.complement(2..4), SortedDisjointIntervalList::empty());
+        assert_eq!(
+            SortedDisjointIntervalList::<()>::empty().complement(0..10),
+            single(0..10)
+        );
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let list = blank(&[0..5, 10..15]);
+        assert!(list.overlaps(&single(4..6)));
+        assert!(list.overlaps(&blank(&[6..8, 14..20])));
+        // the ends are exclusive, so touching isn't overlapping
+        assert!(!list.overlaps(&single(5..10)));
+        assert!(!list.overlaps(&single(15..20)));
+        assert!(!list.overlaps(&SortedDisjointIntervalList::empty()));
+    }
+
+    #[test]
+    fn test_span_of_wide_intervals() {
+        let everything = SortedDisjointIntervalList::new(vec![
+            BlankInterval::new(i128::MIN..0),
+            BlankInterval::new(0..i128::MAX),
+        ]);
+        assert_eq!(everything.span(), u128::MAX);
+
+        let wide = SortedDisjointIntervalList::new(vec![BlankInterval::new(i64::MIN..i64::MAX)]);
+        assert_eq!(wide.span(), u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_from_range_inclusive() {
+        let interval = BlankInterval::<u64>::try_from(1..=4000).unwrap();
+        assert_eq!(interval.range(), &(1..4001));
+        assert_eq!(interval.len(), 4000);
+
+        assert!(BlankInterval::<u64>::try_from(0..=u64::MAX).is_err());
+        let (start, end) = (5, 4);
+        assert!(BlankInterval::<u64>::try_from(start..=end)
+            .unwrap()
+            .is_empty());
+    }
+
+    fn range_map(pieces: &[(ops::Range<i64>, i64)]) -> RangeMap {
+        RangeMap::new(pieces.iter().cloned()).unwrap()
+    }
+
+    #[test]
+    fn test_range_map_then() {
+        let a = range_map(&[(0..10, 5), (20..25, -20)]);
+        let b = range_map(&[(0..3, 100), (8..20, -1)]);
+
+        let ab = a.then(&b);
+        for value in -10..40 {
+            assert_eq!(ab.get(value), b.get(a.get(value)), "value {}", value);
+        }
+        // 0..3 moves to 5..8 and misses `b`, 3..10 moves into 8..15, and 10..20 is only moved
+        // by `b`. 20..25 lands on 0..5, which straddles the start of `b`
+        assert_eq!(
+            ab.pieces()
+                .map(|(range, offset)| (range.clone(), offset))
+                .collect::<Vec<_>>(),
+            [
+                (0..3, 5),
+                (3..10, 4),
+                (10..20, -1),
+                (20..23, 80),
+                (23..25, -20)
+            ]
+        );
+
+        assert_eq!(a.then(&RangeMap::identity()), a);
+        assert_eq!(RangeMap::identity().then(&a), a);
+    }
+
+    #[test]
+    fn test_range_map_inverse() {
+        // swaps 0..5 and 10..15, and shifts 20..30 along by one with 30 wrapping back to 20
+        let map = range_map(&[(0..5, 10), (10..15, -10), (20..30, 1), (30..31, -10)]);
+
+        let inverse = map.inverse().unwrap();
+        for value in -5..40 {
+            assert_eq!(inverse.get(map.get(value)), value, "value {}", value);
+        }
+        assert!(map.then(&inverse).is_identity());
+        assert_eq!(inverse.inverse().unwrap(), map);
+
+        // 0..5 lands on 3..8, on top of 5..8 which stays put
+        assert!(range_map(&[(0..5, 3)]).inverse().is_err());
+        // two ranges landing on the same place
+        assert!(range_map(&[(0..5, 10), (5..10, 5)]).inverse().is_err());
+    }
+
+    #[test]
+    fn test_range_map_preimage() {
+        let map = range_map(&[(10..20, 100), (30..40, -20)]);
+        let list = single(5..45);
+
+        // 10..20 leaves the list, and 30..40 lands in the gap it left behind
+        let preimage = map.preimage(&list);
+        assert_eq!(preimage, blank(&[5..10, 20..45]));
+        for value in -10..160 {
+            assert_eq!(
+                preimage.contains(value),
+                list.contains(map.get(value)),
+                "value {}",
+                value
+            );
+        }
+
+        // a range covering the gap between the two images, which pulls in part of each range
+        // along with the values that stay put
+        let list = single(15..115);
+        assert_eq!(map.preimage(&list), blank(&[10..15, 20..30, 35..115]));
+    }
+}
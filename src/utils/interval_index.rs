@@ -0,0 +1,251 @@
+use std::ops;
+
+use super::{Interval, IntervalBound, SortedDisjointIntervalList};
+
+/// A static index over intervals which may overlap each other, answering which intervals
+/// contain a point or overlap a range in `O(log n + k)` time for `k` results
+///
+/// Every interval keeps its data. Empty intervals are dropped, since nothing can ever find them
+#[derive(Debug, Clone)]
+pub struct IntervalIndex<D, N = i64>
+where
+    D: Clone,
+    N: IntervalBound,
+{
+    intervals: Vec<Interval<D, N>>,
+    /// Indices into `intervals`, sorted by start
+    by_start: Vec<usize>,
+    /// A centered interval tree, with the root first
+    nodes: Vec<Node<N>>,
+}
+
+#[derive(Debug, Clone)]
+struct Node<N> {
+    center: N,
+    /// Every interval containing `center`, sorted by start
+    by_start: Vec<usize>,
+    /// The same intervals, sorted by end from last to first
+    by_end_descending: Vec<usize>,
+    /// Intervals which end at or before `center`
+    left: Option<usize>,
+    /// Intervals which start after `center`
+    right: Option<usize>,
+}
+
+impl<D, N> IntervalIndex<D, N>
+where
+    D: Clone,
+    N: IntervalBound,
+{
+    pub fn new(intervals: Vec<Interval<D, N>>) -> Self {
+        let intervals = intervals
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut by_start = (0..intervals.len()).collect::<Vec<_>>();
+        by_start.sort_by_key(|&i| intervals[i].start());
+
+        let mut index = Self {
+            intervals,
+            by_start: by_start.clone(),
+            nodes: Vec::new(),
+        };
+        index.build(by_start);
+        index
+    }
+
+    /// Add a subtree holding `members` (which are sorted by start), returning its node
+    fn build(&mut self, members: Vec<usize>) -> Option<usize> {
+        if members.is_empty() {
+            return None;
+        }
+
+        // the median start always lies inside its own interval, so every node holds at least one,
+        // and each side gets at most half of the members
+        let center = self.intervals[members[members.len() / 2]].start();
+
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut here = Vec::new();
+        for i in members {
+            let interval = &self.intervals[i];
+            if interval.end() <= center {
+                left.push(i);
+            } else if interval.start() > center {
+                right.push(i);
+            } else {
+                here.push(i);
+            }
+        }
+
+        let mut by_end_descending = here.clone();
+        by_end_descending.sort_by_key(|&i| std::cmp::Reverse(self.intervals[i].end()));
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            center,
+            by_start: here,
+            by_end_descending,
+            left: None,
+            right: None,
+        });
+
+        let left = self.build(left);
+        let right = self.build(right);
+        self.nodes[node].left = left;
+        self.nodes[node].right = right;
+
+        Some(node)
+    }
+
+    pub fn intervals(&self) -> &[Interval<D, N>] {
+        &self.intervals
+    }
+
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Every interval containing `point`, in no particular order
+    pub fn containing(&self, point: N) -> Vec<&Interval<D, N>> {
+        let mut found = Vec::new();
+
+        let mut next = (!self.nodes.is_empty()).then_some(0);
+        while let Some(node) = next {
+            let node = &self.nodes[node];
+            if point < node.center {
+                // every interval here ends after the center, so it only has to start early enough
+                found.extend(
+                    node.by_start
+                        .iter()
+                        .map(|&i| &self.intervals[i])
+                        .take_while(|interval| interval.start() <= point),
+                );
+                next = node.left;
+            } else {
+                // every interval here starts at or before the center, so it only has to end late enough
+                found.extend(
+                    node.by_end_descending
+                        .iter()
+                        .map(|&i| &self.intervals[i])
+                        .take_while(|interval| interval.end() > point),
+                );
+                next = if point > node.center {
+                    node.right
+                } else {
+                    None
+                };
+            }
+        }
+
+        found
+    }
+
+    /// Every interval sharing at least one point with `range`, in no particular order
+    pub fn overlapping(&self, range: &ops::Range<N>) -> Vec<&Interval<D, N>> {
+        if range.is_empty() {
+            return Vec::new();
+        }
+
+        // an interval overlaps the range if it either contains the start of the range,
+        // or starts somewhere after that but still within the range
+        let mut found = self.containing(range.start);
+
+        let first = self
+            .by_start
+            .partition_point(|&i| self.intervals[i].start() <= range.start);
+        found.extend(
+            self.by_start[first..]
+                .iter()
+                .map(|&i| &self.intervals[i])
+                .take_while(|interval| interval.start() < range.end),
+        );
+
+        found
+    }
+}
+
+impl<D, N> FromIterator<Interval<D, N>> for IntervalIndex<D, N>
+where
+    D: Clone,
+    N: IntervalBound,
+{
+    fn from_iter<I: IntoIterator<Item = Interval<D, N>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<D, N> From<&SortedDisjointIntervalList<D, N>> for IntervalIndex<D, N>
+where
+    D: Clone + PartialEq,
+    N: IntervalBound,
+{
+    fn from(list: &SortedDisjointIntervalList<D, N>) -> Self {
+        Self::new(list.intervals().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts<D: Clone>(mut found: Vec<&Interval<D>>) -> Vec<(i64, i64)> {
+        found.sort_by_key(|interval| (interval.start(), interval.end()));
+        found
+            .into_iter()
+            .map(|interval| (interval.start(), interval.end()))
+            .collect()
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let intervals = (0..60)
+            .map(|i: i64| {
+                let start = (i * 37) % 101 - 50;
+                let length = (i * 13) % 17;
+                Interval::new_with_data(start..start + length, i)
+            })
+            .collect::<Vec<_>>();
+        let index = IntervalIndex::new(intervals.clone());
+
+        for point in -60..70 {
+            let expected = intervals
+                .iter()
+                .filter(|interval| interval.contains(point))
+                .collect();
+            assert_eq!(starts(index.containing(point)), starts(expected));
+        }
+
+        for start in -60..70 {
+            for length in 0..20 {
+                let range = start..start + length;
+                let expected = intervals
+                    .iter()
+                    .filter(|interval| interval.overlaps(&Interval::new(range.clone())))
+                    .collect();
+                assert_eq!(starts(index.overlapping(&range)), starts(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_keeps_data() {
+        let index = IntervalIndex::from_iter([
+            Interval::new_with_data(0..10, "a"),
+            Interval::new_with_data(5..15, "b"),
+        ]);
+
+        let mut found = index
+            .containing(7)
+            .into_iter()
+            .flat_map(|interval| interval.data().to_vec())
+            .collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, vec!["a", "b"]);
+    }
+}
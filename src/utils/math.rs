@@ -1,74 +1,311 @@
 use core::fmt;
+use std::ops;
 
-use nalgebra::{Matrix3, Vector3};
+use num::{integer::Roots, BigInt, BigRational, One, Signed, ToPrimitive, Zero};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
-pub struct Point2D {
-    x: f64,
-    y: f64,
+/// A polynomial with exact rational coefficients
+///
+/// The coefficients are stored from the constant term upwards, with no trailing zeros,
+/// so the zero polynomial has no coefficients at all
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Polynomial {
+    coefficients: Vec<BigRational>,
 }
 
-impl Point2D {
-    pub fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+        Self { coefficients }
     }
-}
 
-impl From<(f64, f64)> for Point2D {
-    fn from((x, y): (f64, f64)) -> Self {
-        Self { x, y }
+    pub fn from_integers(coefficients: &[i64]) -> Self {
+        Self::new(coefficients.iter().map(|&c| rational(c)).collect())
+    }
+
+    /// The polynomial of lowest degree passing through every one of `points`
+    ///
+    /// This uses Newton's divided differences, so the x values can be spaced however they like,
+    /// but they must all be different
+    pub fn fit(points: &[(i64, i64)]) -> Result<Self, String> {
+        let xs = points.iter().map(|&(x, _)| rational(x)).collect::<Vec<_>>();
+        for (i, x) in xs.iter().enumerate() {
+            if xs[..i].contains(x) {
+                return Err(format!(
+                    "can't fit a polynomial through two points at x = {}",
+                    x
+                ));
+            }
+        }
+
+        // after the i-th pass, differences[j] holds the divided difference of points j-i..=j
+        let mut differences = points.iter().map(|&(_, y)| rational(y)).collect::<Vec<_>>();
+        for i in 1..points.len() {
+            for j in (i..points.len()).rev() {
+                differences[j] = (&differences[j] - &differences[j - 1]) / (&xs[j] - &xs[j - i]);
+            }
+        }
+
+        Ok(Self::from_newton_form(&differences, &xs))
     }
+
+    /// The polynomial of lowest degree where `p(i) = values[i]` for each index `i`
+    ///
+    /// Evaluating at `-1` extrapolates backwards, and at `values.len()` forwards
+    pub fn fit_sequence(values: &[i64]) -> Self {
+        // with evenly spaced x values, divided differences are just forward differences
+        // scaled by a factorial, which we can leave to the Newton form below
+        let mut layer = values
+            .iter()
+            .map(|&value| BigInt::from(value))
+            .collect::<Vec<_>>();
+        let mut leading_differences = Vec::new();
+        let mut factorial = BigInt::one();
+        while !layer.is_empty() && !layer.iter().all(Zero::is_zero) {
+            leading_differences.push(BigRational::new(layer[0].clone(), factorial.clone()));
+            factorial *= BigInt::from(leading_differences.len());
+            layer = layer.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        }
+
+        let xs = (0..leading_differences.len() as i64)
+            .map(rational)
+            .collect::<Vec<_>>();
+        Self::from_newton_form(&leading_differences, &xs)
+    }
+
+    /// Expand `c0 + c1 (x - x0) + c2 (x - x0)(x - x1) + ...` into ordinary coefficients
+    fn from_newton_form(newton_coefficients: &[BigRational], xs: &[BigRational]) -> Self {
+        let mut coefficients: Vec<BigRational> = Vec::new();
+        for (i, newton_coefficient) in newton_coefficients.iter().enumerate().rev() {
+            // coefficients = coefficients * (x - xs[i]) + newton_coefficient
+            let mut next = vec![BigRational::zero(); coefficients.len() + 1];
+            for (power, coefficient) in coefficients.iter().enumerate() {
+                next[power + 1] += coefficient;
+                next[power] -= coefficient * &xs[i];
+            }
+            next[0] += newton_coefficient;
+            coefficients = next;
+        }
+        Self::new(coefficients)
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    /// The degree of the polynomial, or `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn evaluate(&self, x: &BigRational) -> BigRational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |total, coefficient| {
+                total * x + coefficient
+            })
+    }
+
+    /// Evaluate at an integer, requiring the result to be an integer which fits in an `i64`
+    pub fn evaluate_integer(&self, x: i64) -> Result<i64, String> {
+        let y = self.evaluate(&rational(x));
+        if !y.is_integer() {
+            return Err(format!(
+                "{} evaluates to non-integer {} at x = {}",
+                self, y, x
+            ));
+        }
+        y.to_integer().to_i64().ok_or_else(|| {
+            format!(
+                "{} evaluates to {} at x = {}, which is too large",
+                self, y, x
+            )
+        })
+    }
+}
+
+fn rational(value: i64) -> BigRational {
+    BigRational::from_integer(BigInt::from(value))
 }
 
-impl fmt::Display for Point2D {
+impl fmt::Display for Polynomial {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+        if self.coefficients.is_empty() {
+            return write!(f, "y = 0");
+        }
+
+        write!(f, "y =")?;
+        for (power, coefficient) in self.coefficients.iter().enumerate().rev() {
+            if coefficient.is_zero() {
+                continue;
+            }
+            let is_first = power + 1 == self.coefficients.len();
+            let separator = match (is_first, coefficient.is_negative()) {
+                (true, false) => " ",
+                (true, true) => " -",
+                (false, false) => " + ",
+                (false, true) => " - ",
+            };
+            write!(f, "{}", separator)?;
+            match power {
+                0 => write!(f, "{}", coefficient.abs())?,
+                1 => write!(f, "{}x", coefficient.abs())?,
+                _ => write!(f, "{}x^{}", coefficient.abs(), power)?,
+            }
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct QuadraticEquation {
-    a: f64,
-    b: f64,
-    c: f64,
+/// Every integer `t` where `a * t^2 + b * t + c > 0`, for a parabola opening downwards (`a < 0`)
+///
+/// This is exact, using only integer arithmetic. Gives `None` if there are no such integers,
+/// or an error if `a` isn't negative or the arithmetic would overflow an `i128`
+pub fn quadratic_positive_interval(
+    a: i128,
+    b: i128,
+    c: i128,
+) -> Result<Option<ops::RangeInclusive<i128>>, String> {
+    if a >= 0 {
+        return Err(format!(
+            "{}t^2 + {}t + {} > 0 isn't a bounded interval unless the t^2 coefficient is negative",
+            a, b, c
+        ));
+    }
+    let overflow = || format!("{}t^2 + {}t + {} overflows an i128", a, b, c);
+    let f = |t: i128| -> Result<i128, String> {
+        a.checked_mul(t)
+            .and_then(|at| at.checked_add(b))
+            .and_then(|atb| atb.checked_mul(t))
+            .and_then(|atbt| atbt.checked_add(c))
+            .ok_or_else(overflow)
+    };
+
+    let discriminant = b
+        .checked_mul(b)
+        .and_then(|bb| {
+            a.checked_mul(c)?
+                .checked_mul(4)
+                .and_then(|ac4| bb.checked_sub(ac4))
+        })
+        .ok_or_else(overflow)?;
+    if discriminant <= 0 {
+        // the peak of the parabola doesn't rise above zero
+        return Ok(None);
+    }
+
+    // with a < 0, the roots are (b -+ sqrt(discriminant)) / 2|a|, and f is positive between them
+    let two_abs_a = -2 * a;
+    let root = (discriminant as u128).sqrt() as i128;
+
+    // the highest integer point is next to the real peak at b / 2|a|
+    let vertex = b.div_euclid(two_abs_a);
+    let peak = if f(vertex)? >= f(vertex + 1)? {
+        vertex
+    } else {
+        vertex + 1
+    };
+    if f(peak)? <= 0 {
+        return Ok(None);
+    }
+
+    // the square root is rounded down, so these guesses can be slightly off in either direction
+    let mut low = (b - root).div_euclid(two_abs_a).min(peak);
+    while f(low)? <= 0 {
+        low += 1;
+    }
+    while f(low - 1)? > 0 {
+        low -= 1;
+    }
+
+    let mut high = (b + root).div_euclid(two_abs_a).max(peak);
+    while f(high)? <= 0 {
+        high -= 1;
+    }
+    while f(high + 1)? > 0 {
+        high += 1;
+    }
+
+    Ok(Some(low..=high))
 }
 
-impl QuadraticEquation {
-    pub fn new(a: f64, b: f64, c: f64) -> Self {
-        Self { a, b, c }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_finds_minimal_degree() {
+        let line = Polynomial::fit(&[(0, 1), (2, 5), (5, 11), (9, 19)]).unwrap();
+        assert_eq!(line, Polynomial::from_integers(&[1, 2]));
+        assert_eq!(line.degree(), Some(1));
+
+        let constant = Polynomial::fit_sequence(&[7, 7, 7, 7]);
+        assert_eq!(constant.degree(), Some(0));
+        assert_eq!(Polynomial::fit_sequence(&[0, 0]).degree(), None);
     }
 
-    pub fn from_points(points: &[Point2D; 3]) -> Result<Self, String> {
-        let Point2D { x: x1, y: y1 } = points[0];
-        let Point2D { x: x2, y: y2 } = points[1];
-        let Point2D { x: x3, y: y3 } = points[2];
+    #[test]
+    fn test_fit_sequence_matches_fit() {
+        let values = [1, 3, 6, 10, 15, 21];
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(x, &y)| (x as i64, y))
+            .collect::<Vec<_>>();
 
-        let equation_matrix = // this comment breaks the line to make it readable
-        Matrix3::new(x1 * x1, x1, 1.0, x2 * x2, x2, 1.0, x3 * x3, x3, 1.0);
+        let sequence = Polynomial::fit_sequence(&values);
+        assert_eq!(sequence, Polynomial::fit(&points).unwrap());
+        assert_eq!(sequence.degree(), Some(2));
+        assert_eq!(sequence.evaluate_integer(6), Ok(28));
+        assert_eq!(sequence.evaluate_integer(-1), Ok(0));
+    }
 
-        let y_vector = Vector3::new(y1, y2, y3);
+    #[test]
+    fn test_evaluate_far_away() {
+        // (x^2 + x) / 2 needs exact arithmetic to stay an integer
+        let triangle = Polynomial::fit(&[(65, 2145), (196, 19306), (327, 53628)]).unwrap();
 
-        if let Some(coefficients) = equation_matrix.try_inverse().map(|inv| inv * y_vector) {
-            Ok(Self {
-                a: coefficients[0],
-                b: coefficients[1],
-                c: coefficients[2],
-            })
-        } else {
-            Err(format!(
-                "Points {:?} cannot form a quadratic equation",
-                points
-            ))
-        }
+        let x = 26_501_365;
+        assert_eq!(triangle.evaluate_integer(x), Ok(x * (x + 1) / 2));
+        assert!(Polynomial::fit(&[(0, 0), (0, 1)]).is_err());
     }
 
-    pub fn solve_for_y(&self, x: f64) -> f64 {
-        self.a * (x * x) + self.b * x + self.c
+    #[test]
+    fn test_quadratic_positive_interval_matches_brute_force() {
+        for a in -4..0 {
+            for b in -30..=30 {
+                for c in -30..=30 {
+                    let positive = (-50..=50)
+                        .filter(|&t| a * t * t + b * t + c > 0)
+                        .collect::<Vec<i128>>();
+                    let expected = positive.first().map(|&low| low..=*positive.last().unwrap());
+
+                    assert_eq!(
+                        quadratic_positive_interval(a, b, c),
+                        Ok(expected),
+                        "{}t^2 + {}t + {} > 0",
+                        a,
+                        b,
+                        c
+                    );
+                }
+            }
+        }
     }
-}
 
-impl fmt::Display for QuadraticEquation {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "y = {}x^2 + {}x + {}", self.a, self.b, self.c)
+    #[test]
+    fn test_quadratic_positive_interval_limits() {
+        assert!(quadratic_positive_interval(1, 0, -1).is_err());
+        assert!(quadratic_positive_interval(-1, i128::MAX, 0).is_err());
+
+        // a huge race: t * (T - t) > record
+        let time = 1_000_000_000_000i128;
+        let record = time * time / 4 - 1;
+        assert_eq!(
+            quadratic_positive_interval(-1, time, -record),
+            Ok(Some(time / 2..=time / 2))
+        );
     }
 }
@@ -1,8 +1,634 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-pub fn integers_from_string<T: FromStr>(input: &str, delim: &str) -> Vec<T> {
+/// Something in the input that didn't match what a parser expected, and where it was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the problem is on, counting from 1
+    pub line: usize,
+    /// The character on that line where the problem starts, counting from 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} col {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl From<ParseError> for String {
+    fn from(error: ParseError) -> Self {
+        error.to_string()
+    }
+}
+
+/// A position in some input text, which moves forward as pieces of it are parsed
+///
+/// Cursors are cheap to copy, so backtracking is just holding on to an old copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    position: usize,
+    first_line: usize,
+    first_column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::at_line(input, 1)
+    }
+
+    /// A cursor over `input`, which starts on line `line` of some larger text
+    pub fn at_line(input: &'a str, line: usize) -> Self {
+        Self::at(input, line, 1)
+    }
+
+    /// A cursor over `input`, which starts part way along line `line` of some larger text
+    pub fn at(input: &'a str, line: usize, column: usize) -> Self {
+        Self {
+            input,
+            position: 0,
+            first_line: line,
+            first_column: column,
+        }
+    }
+
+    /// Everything which hasn't been parsed yet
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// The line and column the cursor is at
+    pub fn location(&self) -> (usize, usize) {
+        let parsed = &self.input[..self.position];
+        let line = self.first_line + parsed.matches('\n').count();
+        let column = match parsed.rfind('\n') {
+            Some(newline) => parsed[newline + 1..].chars().count() + 1,
+            None => self.first_column + parsed.chars().count(),
+        };
+        (line, column)
+    }
+
+    /// An error at the cursor's current position
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        let (line, column) = self.location();
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error saying what was expected, and what is actually next in the input
+    fn expected(&self, expected: &str, length: usize) -> ParseError {
+        let found = self.rest().chars().take(length.max(1)).collect::<String>();
+        if found.is_empty() {
+            self.error(format!("expected {} found end of input", expected))
+        } else {
+            self.error(format!("expected {} found '{}'", expected, found))
+        }
+    }
+
+    fn advance(&mut self, bytes: usize) -> &'a str {
+        let taken = &self.rest()[..bytes];
+        self.position += bytes;
+        taken
+    }
+
+    /// Consume `literal`, if it's next
+    pub fn eat_literal(&mut self, literal: &str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.advance(literal.len());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume `literal`, or fail if something else is next
+    pub fn expect_literal(&mut self, literal: &str) -> Result<(), ParseError> {
+        if self.eat_literal(literal) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("'{}'", literal), literal.chars().count()))
+        }
+    }
+
+    /// Consume the longest run of characters matching `predicate`, which may be empty
+    pub fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let length = self
+            .rest()
+            .find(|c: char| !predicate(c))
+            .unwrap_or(self.rest().len());
+        self.advance(length)
+    }
+
+    /// Consume any spaces or tabs
+    pub fn skip_spaces(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    /// Consume at least one space or tab
+    pub fn spaces(&mut self) -> Result<(), ParseError> {
+        if self.take_while(|c| c == ' ' || c == '\t').is_empty() {
+            return Err(self.expected("' '", 1));
+        }
+        Ok(())
+    }
+
+    /// Consume an integer, with an optional sign, and parse it as a `T`
+    pub fn integer<T>(&mut self) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let start = *self;
+        let sign = if self.eat_literal("-") {
+            "-"
+        } else {
+            self.eat_literal("+");
+            ""
+        };
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            *self = start;
+            return Err(self.expected("integer", 1));
+        }
+
+        format!("{}{}", sign, digits)
+            .parse::<T>()
+            .map_err(|err| start.error(format!("invalid integer '{}{}': {}", sign, digits, err)))
+    }
+
+    /// Consume a name made of letters, digits and underscores
+    pub fn identifier(&mut self) -> Result<&'a str, ParseError> {
+        let identifier = self.take_while(|c| c.is_alphanumeric() || c == '_');
+        if identifier.is_empty() {
+            return Err(self.expected("identifier", 1));
+        }
+        Ok(identifier)
+    }
+
+    /// Consume one or more items, with a separator between each of them
+    ///
+    /// The list ends at the first separator which isn't followed by another item, leaving the
+    /// cursor just before that separator
+    pub fn separated_list<T>(
+        &mut self,
+        mut separator: impl FnMut(&mut Self) -> Result<(), ParseError>,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        loop {
+            let before_separator = *self;
+            let next = separator(self).and_then(|_| item(self));
+            match next {
+                Ok(next) => items.push(next),
+                Err(_) => {
+                    *self = before_separator;
+                    return Ok(items);
+                }
+            }
+        }
+    }
+
+    /// Fail unless everything has been parsed
+    pub fn expect_end(&self) -> Result<(), ParseError> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            Err(self.expected("end of line", 1))
+        }
+    }
+}
+
+/// Parse every line of `input` with `parse_line`, which has to use up the whole line
+///
+/// Errors point at the line and column in `input` where parsing failed
+pub fn line_by_line<'a, T>(
+    input: &'a str,
+    parse_line: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    line_by_line_from(input, 1, 1, parse_line)
+}
+
+/// Like [`line_by_line`], for `input` which starts part way through some larger text
+fn line_by_line_from<'a, T>(
+    input: &'a str,
+    first_line: usize,
+    first_column: usize,
+    mut parse_line: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
     input
-        .split(delim)
-        .filter_map(|x| x.parse::<T>().ok())
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let column = if index == 0 { first_column } else { 1 };
+            let mut cursor = Cursor::at(line, first_line + index, column);
+            let parsed = parse_line(&mut cursor)?;
+            cursor.expect_end()?;
+            Ok(parsed)
+        })
         .collect()
 }
+
+/// A run of lines with no blank lines in it, cut out of some larger text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// Which section this is, counting from 1
+    pub number: usize,
+    /// The line of the larger text this section starts on, counting from 1
+    pub first_line: usize,
+    /// The column on `first_line` this section starts at, counting from 1
+    pub first_column: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    /// A cursor at the start of the section, which reports locations in the larger text
+    pub fn cursor(&self) -> Cursor<'a> {
+        Cursor::at(self.text, self.first_line, self.first_column)
+    }
+
+    /// Parse the whole section at once, marking any error with the section it came from
+    pub fn parse<T, E: fmt::Display>(
+        &self,
+        parse: impl FnOnce(&'a str) -> Result<T, E>,
+    ) -> Result<T, String> {
+        parse(self.text).map_err(|err| format!("section {}: {}", self.number, err))
+    }
+
+    /// Parse each line of the section with `parse_line`, like [`line_by_line`]
+    pub fn lines<T>(
+        &self,
+        parse_line: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, String> {
+        line_by_line_from(self.text, self.first_line, self.first_column, parse_line)
+            .map_err(|err| format!("section {}: {}", self.number, err))
+    }
+}
+
+/// Split `input` into sections wherever there are blank lines, with either `\n` or `\r\n`
+/// line endings
+///
+/// Runs of several blank lines count as one break, and sections never include the line ending
+/// after their last line
+pub fn sections(input: &str) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    // the byte range and first line of the section we're in the middle of, if any
+    let mut current: Option<(usize, usize, usize)> = None;
+    let mut offset = 0;
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\r', '\n']);
+        if content.trim().is_empty() {
+            if let Some((start, end, first_line)) = current.take() {
+                sections.push(Section {
+                    number: sections.len() + 1,
+                    first_line,
+                    first_column: 1,
+                    text: &input[start..end],
+                });
+            }
+        } else {
+            let end = offset + content.len();
+            match &mut current {
+                Some((_, current_end, _)) => *current_end = end,
+                None => current = Some((offset, end, index + 1)),
+            }
+        }
+        offset += line.len();
+    }
+    if let Some((start, end, first_line)) = current {
+        sections.push(Section {
+            number: sections.len() + 1,
+            first_line,
+            first_column: 1,
+            text: &input[start..end],
+        });
+    }
+    sections
+}
+
+/// Sections which each start with a `name:` header, looked up by name
+///
+/// Anything after the colon on the header line is the start of the body, so both
+/// `seeds: 1 2 3` and a `seed-to-soil map:` line followed by a table work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderBlocks<'a> {
+    blocks: Vec<HeaderBlock<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderBlock<'a> {
+    pub name: &'a str,
+    pub body: Section<'a>,
+}
+
+impl<'a> HeaderBlocks<'a> {
+    pub fn parse(input: &'a str) -> Result<Self, String> {
+        let mut blocks: Vec<HeaderBlock<'a>> = Vec::new();
+        for section in sections(input) {
+            let header = section.text.lines().next().unwrap_or_default();
+            let (name, inline) = header.split_once(':').ok_or(format!(
+                "section {}: line {}: expected a 'name:' header, found '{}'",
+                section.number, section.first_line, header
+            ))?;
+            let name = name.trim();
+            if blocks.iter().any(|block| block.name == name) {
+                return Err(format!(
+                    "section {}: header '{}' appears more than once",
+                    section.number, name
+                ));
+            }
+
+            // the body is whatever follows the colon, skipping straight to the next line if
+            // there's nothing else on the header line
+            let inline_start = header.len() - inline.len();
+            let body = if inline.trim().is_empty() {
+                let next_line = section.text[header.len()..].trim_start_matches(['\r', '\n']);
+                Section {
+                    number: section.number,
+                    first_line: section.first_line + 1,
+                    first_column: 1,
+                    text: next_line,
+                }
+            } else {
+                let text = section.text[inline_start..].trim_start();
+                Section {
+                    number: section.number,
+                    first_line: section.first_line,
+                    first_column: header[..section.text.len() - text.len()].chars().count() + 1,
+                    text,
+                }
+            };
+
+            blocks.push(HeaderBlock { name, body });
+        }
+
+        Ok(Self { blocks })
+    }
+
+    pub fn blocks(&self) -> &[HeaderBlock<'a>] {
+        &self.blocks
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Section<'a>> {
+        self.blocks
+            .iter()
+            .find(|block| block.name == name)
+            .map(|block| &block.body)
+    }
+
+    /// The body under the header `name`, or an error if there isn't one
+    pub fn expect(&self, name: &str) -> Result<&Section<'a>, String> {
+        self.get(name)
+            .ok_or_else(|| format!("missing a '{}:' section", name))
+    }
+}
+
+/// How a graph is written out with one node per line, like `name -> a, b`, `name = (a, b)`
+/// or `name: a b`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjacencyFormat<'s> {
+    /// Characters which may come right before a name to give it a type, like the `%` in `%a`
+    sigils: &'s [char],
+    /// What comes between the name and its list of neighbors
+    separator: &'s str,
+    /// What wraps the list of neighbors, if anything
+    brackets: Option<(&'s str, &'s str)>,
+    /// What comes between each neighbor
+    list_separator: &'s str,
+}
+
+/// One line of an adjacency list: a node, and the nodes it has edges to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjacencyLine<'a> {
+    /// The line this node was on, counting from 1
+    pub line: usize,
+    pub sigil: Option<char>,
+    pub name: &'a str,
+    pub neighbors: Vec<&'a str>,
+}
+
+impl<'s> AdjacencyFormat<'s> {
+    /// `name -> a, b`
+    pub fn arrow() -> Self {
+        Self {
+            sigils: &[],
+            separator: " -> ",
+            brackets: None,
+            list_separator: ", ",
+        }
+    }
+
+    /// `name = (a, b)`
+    pub fn assignment() -> Self {
+        Self {
+            sigils: &[],
+            separator: " = ",
+            brackets: Some(("(", ")")),
+            list_separator: ", ",
+        }
+    }
+
+    /// `name: a b`
+    pub fn colon() -> Self {
+        Self {
+            sigils: &[],
+            separator: ": ",
+            brackets: None,
+            list_separator: " ",
+        }
+    }
+
+    /// Allow any of `sigils` right before each node's name
+    pub fn with_sigils(self, sigils: &'s [char]) -> Self {
+        Self { sigils, ..self }
+    }
+
+    /// Parse every line of `input` as a node and its neighbors
+    pub fn parse<'a>(&self, input: &'a str) -> Result<Vec<AdjacencyLine<'a>>, ParseError> {
+        line_by_line(input, |cursor| self.parse_line(cursor))
+    }
+
+    /// Parse a single node and its neighbors
+    pub fn parse_line<'a>(&self, cursor: &mut Cursor<'a>) -> Result<AdjacencyLine<'a>, ParseError> {
+        let (line, _) = cursor.location();
+        let sigil = cursor.peek().filter(|c| self.sigils.contains(c));
+        if let Some(sigil) = sigil {
+            cursor.expect_literal(&sigil.to_string())?;
+        }
+        let name = cursor.identifier()?;
+        cursor.expect_literal(self.separator)?;
+
+        if let Some((open, _)) = self.brackets {
+            cursor.expect_literal(open)?;
+        }
+        let neighbors = cursor.separated_list(
+            |cursor| cursor.expect_literal(self.list_separator),
+            |cursor| cursor.identifier(),
+        )?;
+        if let Some((_, close)) = self.brackets {
+            cursor.expect_literal(close)?;
+        }
+
+        Ok(AdjacencyLine {
+            line,
+            sigil,
+            name,
+            neighbors,
+        })
+    }
+}
+
+impl<'a> AdjacencyLine<'a> {
+    /// Every edge out of this node, as `(from, to, position in the list)`, ready to be
+    /// collected into a graph
+    pub fn edges(&self) -> impl Iterator<Item = (&'a str, &'a str, usize)> + '_ {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .map(|(position, &neighbor)| (self.name, neighbor, position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_parses_a_line() {
+        let mut cursor = Cursor::new("Game 12: -3, 4, 5 rest");
+
+        cursor.expect_literal("Game").unwrap();
+        cursor.spaces().unwrap();
+        assert_eq!(cursor.integer::<u32>(), Ok(12));
+        cursor.expect_literal(": ").unwrap();
+        let numbers = cursor.separated_list(|c| c.expect_literal(", "), |c| c.integer::<i64>());
+        assert_eq!(numbers, Ok(vec![-3, 4, 5]));
+        cursor.skip_spaces();
+        assert_eq!(cursor.identifier(), Ok("rest"));
+        assert!(cursor.expect_end().is_ok());
+    }
+
+    #[test]
+    fn test_errors_report_location() {
+        let error = line_by_line("1,2\n3,4\n5 6", |cursor| {
+            let a = cursor.integer::<i32>()?;
+            cursor.expect_literal(",")?;
+            Ok((a, cursor.integer::<i32>()?))
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "line 3 col 2: expected ',' found ' '");
+
+        let error = Cursor::new("x").integer::<u8>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1 col 1: expected integer found 'x'"
+        );
+
+        let mut cursor = Cursor::new("ab\n 300");
+        cursor.expect_literal("ab\n ").unwrap();
+        let error = cursor.integer::<u8>().unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+
+        let error = line_by_line("1 2", |cursor| cursor.integer::<i32>()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1 col 2: expected end of line found ' '"
+        );
+    }
+
+    #[test]
+    fn test_sections_handle_both_line_endings() {
+        for newline in ["\n", "\r\n"] {
+            let input = ["a", "b", "", "", "c", "", "d", ""].join(newline);
+
+            let sections = sections(&input);
+            let texts = sections
+                .iter()
+                .map(|section| section.text)
+                .collect::<Vec<_>>();
+            assert_eq!(texts, vec![["a", "b"].join(newline).as_str(), "c", "d"]);
+            assert_eq!(sections[1].number, 2);
+            assert_eq!(sections[1].first_line, 5);
+        }
+    }
+
+    #[test]
+    fn test_header_blocks() {
+        let input = "seeds: 1 2 x\n\nsoil map:\n1 2\n3 4\n";
+        let blocks = HeaderBlocks::parse(input).unwrap();
+
+        assert_eq!(
+            blocks
+                .blocks()
+                .iter()
+                .map(|block| block.name)
+                .collect::<Vec<_>>(),
+            vec!["seeds", "soil map"]
+        );
+        let rows = blocks.expect("soil map").unwrap().lines(|cursor| {
+            let a = cursor.integer::<i32>()?;
+            cursor.spaces()?;
+            Ok((a, cursor.integer::<i32>()?))
+        });
+        assert_eq!(rows, Ok(vec![(1, 2), (3, 4)]));
+
+        // errors point at the right place in the whole input
+        let seeds = blocks.expect("seeds").unwrap().lines(|cursor| {
+            cursor.separated_list(|cursor| cursor.spaces(), |cursor| cursor.integer::<i32>())
+        });
+        assert_eq!(
+            seeds,
+            Err("section 1: line 1 col 11: expected end of line found ' '".to_string())
+        );
+        assert!(blocks.expect("water map").is_err());
+        assert!(HeaderBlocks::parse("no header\n").is_err());
+    }
+
+    #[test]
+    fn test_adjacency_formats() {
+        let lines = AdjacencyFormat::assignment()
+            .parse("AAA = (BBB, CCC)\nBBB = (DDD, EEE)")
+            .unwrap();
+        assert_eq!(lines[1].name, "BBB");
+        assert_eq!(lines[1].neighbors, vec!["DDD", "EEE"]);
+        assert_eq!(lines[1].line, 2);
+
+        let lines = AdjacencyFormat::arrow()
+            .with_sigils(&['%', '&'])
+            .parse("broadcaster -> a, b\n%a -> b")
+            .unwrap();
+        assert_eq!(lines[0].sigil, None);
+        assert_eq!(lines[1].sigil, Some('%'));
+        assert_eq!(
+            lines
+                .iter()
+                .flat_map(AdjacencyLine::edges)
+                .collect::<Vec<_>>(),
+            vec![
+                ("broadcaster", "a", 0),
+                ("broadcaster", "b", 1),
+                ("a", "b", 0)
+            ]
+        );
+
+        let error = AdjacencyFormat::colon()
+            .parse("jqt: rhn\nrsh frs")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 2 col 4: expected ': ' found ' f'");
+    }
+}
@@ -0,0 +1,234 @@
+use super::{GridPoint, Winding};
+
+/// A closed polygon, described by its vertices in order
+///
+/// The last vertex connects back to the first, so it shouldn't be repeated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon<P> {
+    vertices: Vec<P>,
+}
+
+impl<P> Polygon<P> {
+    pub fn new(vertices: Vec<P>) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[P] {
+        &self.vertices
+    }
+
+    /// Every edge of the polygon as a pair of vertices, including the closing edge
+    pub fn edges(&self) -> impl Iterator<Item = (&P, &P)> {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (&self.vertices[i], &self.vertices[(i + 1) % n]))
+    }
+}
+
+/// Lattice polygons, whose vertices all lie on integer coordinates
+///
+/// All arithmetic is done with `i128`, which is exact as long as the vertices stay within ±2^61,
+/// so that twice the area of anything they can enclose still fits
+impl Polygon<GridPoint> {
+    /// Twice the signed area, as given by the shoelace formula
+    ///
+    /// With y pointing downwards (as it does in a [`Grid`](super::Grid)), this is positive when
+    /// the polygon is traced clockwise. The area is doubled so it is always a whole number
+    pub fn doubled_signed_area(&self) -> i128 {
+        // the terms and running total can overflow on their way to a total which doesn't, so
+        // they wrap, which still comes out exact in the end
+        self.edges()
+            .map(|(a, b)| (a.x as i128 * b.y as i128).wrapping_sub(b.x as i128 * a.y as i128))
+            .fold(0, i128::wrapping_add)
+    }
+
+    /// Twice the unsigned area
+    pub fn doubled_area(&self) -> u128 {
+        self.doubled_signed_area().unsigned_abs()
+    }
+
+    /// Which way the polygon turns as it is traced, or `None` if it has no area
+    pub fn winding(&self) -> Option<Winding> {
+        match self.doubled_signed_area() {
+            0 => None,
+            area if area > 0 => Some(Winding::Right),
+            _ => Some(Winding::Left),
+        }
+    }
+
+    /// The number of lattice points lying on the boundary of the polygon
+    ///
+    /// When every edge is horizontal or vertical, this is also the length of the boundary
+    pub fn boundary_length(&self) -> u128 {
+        self.edges()
+            .map(|(a, b)| {
+                let dx = (b.x as i128 - a.x as i128).unsigned_abs();
+                let dy = (b.y as i128 - a.y as i128).unsigned_abs();
+                num::integer::gcd(dx, dy)
+            })
+            .sum()
+    }
+
+    /// The number of lattice points strictly inside the polygon, using Pick's theorem
+    ///
+    /// The polygon must be simple (it can't cross itself). This is `None` when it has no area,
+    /// like when all its vertices are in a line, since Pick's theorem doesn't hold for those
+    pub fn interior_points(&self) -> Option<u128> {
+        let doubled_area = self.doubled_area();
+        if doubled_area == 0 {
+            return None;
+        }
+
+        // Pick's theorem: A = I + B/2 - 1, so 2I = 2A - B + 2
+        let doubled_interior = (doubled_area + 2).checked_sub(self.boundary_length())?;
+        Some(doubled_interior / 2)
+    }
+
+    /// The number of lattice points inside or on the boundary of the polygon
+    ///
+    /// This is the "area" of a shape drawn on a grid, where the boundary itself takes up whole
+    /// cells. Like [`interior_points`](Self::interior_points), it's `None` when there's no area
+    pub fn area_including_boundary(&self) -> Option<u128> {
+        Some(self.interior_points()? + self.boundary_length())
+    }
+
+    pub fn is_on_boundary(&self, point: &GridPoint) -> bool {
+        let (px, py) = (point.x as i128, point.y as i128);
+        self.edges().any(|(a, b)| {
+            let (ax, ay, bx, by) = (a.x as i128, a.y as i128, b.x as i128, b.y as i128);
+            let cross = (bx - ax) * (py - ay) - (by - ay) * (px - ax);
+            cross == 0
+                && px >= ax.min(bx)
+                && px <= ax.max(bx)
+                && py >= ay.min(by)
+                && py <= ay.max(by)
+        })
+    }
+
+    /// Whether `point` lies inside the polygon or on its boundary
+    pub fn contains(&self, point: &GridPoint) -> bool {
+        self.is_on_boundary(point) || self.strictly_contains(point)
+    }
+
+    /// Whether `point` lies inside the polygon, not counting its boundary
+    pub fn strictly_contains(&self, point: &GridPoint) -> bool {
+        if self.is_on_boundary(point) {
+            return false;
+        }
+
+        // cast a ray towards +x and count how many edges it crosses
+        let (px, py) = (point.x as i128, point.y as i128);
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let (ax, ay, bx, by) = (a.x as i128, a.y as i128, b.x as i128, b.y as i128);
+            if (ay > py) == (by > py) {
+                continue;
+            }
+
+            // the ray crosses if the edge is to the right of the point at height py,
+            // which is compared without dividing by multiplying through by dy
+            let dy = by - ay;
+            let lhs = (px - ax) * dy;
+            let rhs = (py - ay) * (bx - ax);
+            if (dy > 0 && lhs < rhs) || (dy < 0 && lhs > rhs) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
+
+impl<P> From<Vec<P>> for Polygon<P> {
+    fn from(vertices: Vec<P>) -> Self {
+        Self::new(vertices)
+    }
+}
+
+impl<P> FromIterator<P> for Polygon<P> {
+    fn from_iter<I: IntoIterator<Item = P>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(i64, i64)]) -> Polygon<GridPoint> {
+        points.iter().map(|&point| point.into()).collect()
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let rectangle = polygon(&[(0, 0), (4, 0), (4, 3), (0, 3)]);
+
+        assert_eq!(rectangle.doubled_signed_area(), 24);
+        assert_eq!(rectangle.winding(), Some(Winding::Right));
+        assert_eq!(rectangle.boundary_length(), 14);
+        assert_eq!(rectangle.interior_points(), Some(6));
+        assert_eq!(rectangle.area_including_boundary(), Some(20));
+    }
+
+    #[test]
+    fn test_reversed_winding() {
+        let rectangle = polygon(&[(0, 3), (4, 3), (4, 0), (0, 0)]);
+
+        assert_eq!(rectangle.doubled_signed_area(), -24);
+        assert_eq!(rectangle.winding(), Some(Winding::Left));
+        assert_eq!(rectangle.area_including_boundary(), Some(20));
+    }
+
+    #[test]
+    fn test_diagonal_edges() {
+        let triangle = polygon(&[(0, 0), (4, 0), (0, 4)]);
+
+        assert_eq!(triangle.doubled_area(), 16);
+        assert_eq!(triangle.boundary_length(), 12);
+        assert_eq!(triangle.interior_points(), Some(3));
+    }
+
+    #[test]
+    fn test_no_area() {
+        let line = polygon(&[(0, 0), (4, 0)]);
+        assert_eq!(line.doubled_area(), 0);
+        assert_eq!(line.boundary_length(), 8);
+        assert_eq!(line.interior_points(), None);
+        assert_eq!(line.area_including_boundary(), None);
+
+        let collinear = polygon(&[(0, 0), (2, 2), (5, 5)]);
+        assert_eq!(collinear.winding(), None);
+        assert_eq!(collinear.interior_points(), None);
+
+        assert_eq!(polygon(&[(3, 3)]).interior_points(), None);
+        assert_eq!(polygon(&[]).area_including_boundary(), None);
+    }
+
+    #[test]
+    fn test_contains() {
+        let l_shape = polygon(&[(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (0, 4)]);
+
+        assert!(l_shape.strictly_contains(&(1, 1).into()));
+        assert!(l_shape.strictly_contains(&(3, 3).into()));
+        assert!(!l_shape.contains(&(3, 1).into()));
+        assert!(!l_shape.strictly_contains(&(2, 1).into()));
+        assert!(l_shape.contains(&(2, 1).into()));
+        assert!(!l_shape.contains(&(5, 2).into()));
+    }
+
+    #[test]
+    fn test_huge_coordinates() {
+        let max = 1 << 61;
+        let square = polygon(&[(-max, -max), (max, -max), (max, max), (-max, max)]);
+
+        let side = 2 * max as u128;
+        assert_eq!(square.doubled_area(), 2 * side * side);
+        assert_eq!(
+            square.area_including_boundary(),
+            Some((side + 1) * (side + 1))
+        );
+
+        // a sliver out at the edge of the range, whose tiny area is what's left after the huge
+        // terms cancel out
+        let sliver = polygon(&[(max, max), (-max, -max + 1), (-max, -max), (max, max - 1)]);
+        assert_eq!(sliver.doubled_area(), 4 * max as u128);
+    }
+}
@@ -0,0 +1,403 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+    ops::Add,
+};
+
+use num::Zero;
+
+/// The result of a successful [`shortest_path`] search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPath<S, C> {
+    /// Every state along the path, from a start state to a goal state (both inclusive)
+    pub path: Vec<S>,
+    pub cost: C,
+    /// How many states were expanded (had their successors generated) during the search
+    pub expanded: usize,
+}
+
+/// The result of a successful [`all_shortest_paths`] search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllShortestPaths<S, C> {
+    /// Every distinct path of optimal cost, from any start state to any goal state
+    pub paths: Vec<Vec<S>>,
+    pub cost: C,
+    /// How many states were expanded (had their successors generated) during the search
+    pub expanded: usize,
+}
+
+/// Find the cheapest path through a state space using A*
+///
+/// - `starts` are all the states the path may begin from, each at zero cost
+/// - `successors` gives every state reachable from a state, along with the cost of the move
+/// - `is_goal` decides which states the path may end at, so there may be many goals
+/// - `heuristic` estimates the remaining cost to the nearest goal. It must never overestimate,
+///   and must be consistent (never drop by more than the cost of a move). Pass `|_| 0` for
+///   a plain Dijkstra search
+pub fn shortest_path<S, C, IS, FN, IN, FG, FH>(
+    starts: IS,
+    successors: FN,
+    is_goal: FG,
+    heuristic: FH,
+) -> Option<ShortestPath<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    IS: IntoIterator<Item = S>,
+    FN: FnMut(&S) -> IN,
+    IN: IntoIterator<Item = (S, C)>,
+    FG: FnMut(&S) -> bool,
+    FH: FnMut(&S) -> C,
+{
+    let search = Search::run(starts, successors, is_goal, heuristic, false)?;
+
+    let goal = search.goals[0];
+    let mut path = vec![goal];
+    let mut current = goal;
+    while let Some(&parent) = search.parents[current].first() {
+        path.push(parent);
+        current = parent;
+    }
+
+    Some(ShortestPath {
+        path: path
+            .into_iter()
+            .rev()
+            .map(|i| search.states[i].clone())
+            .collect(),
+        cost: search.cost,
+        expanded: search.expanded,
+    })
+}
+
+/// Like [`shortest_path`], but finds every path that ties for the cheapest cost
+///
+/// The number of optimal paths can grow exponentially with their length, so this is only
+/// suitable when few ties are expected
+///
+/// Every tie is found as long as the heuristic is consistent (it never drops by more than the
+/// cost of a step). The exception is a zero-cost step into a state other than a goal: those
+/// states never take a parent that was closed after them, which keeps every path finite even
+/// with zero-cost cycles, but it means ties through such a step can be missed
+pub fn all_shortest_paths<S, C, IS, FN, IN, FG, FH>(
+    starts: IS,
+    successors: FN,
+    is_goal: FG,
+    heuristic: FH,
+) -> Option<AllShortestPaths<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    IS: IntoIterator<Item = S>,
+    FN: FnMut(&S) -> IN,
+    IN: IntoIterator<Item = (S, C)>,
+    FG: FnMut(&S) -> bool,
+    FH: FnMut(&S) -> C,
+{
+    let search = Search::run(starts, successors, is_goal, heuristic, true)?;
+
+    // walk backwards from every goal, branching at every tied parent
+    let mut paths = Vec::new();
+    let mut partials: Vec<Vec<usize>> = search.goals.iter().map(|&goal| vec![goal]).collect();
+    while let Some(partial) = partials.pop() {
+        let current = *partial.last().unwrap();
+        let parents = &search.parents[current];
+        if parents.is_empty() {
+            paths.push(
+                partial
+                    .into_iter()
+                    .rev()
+                    .map(|i| search.states[i].clone())
+                    .collect(),
+            );
+            continue;
+        }
+        for &parent in parents {
+            let mut extended = partial.clone();
+            extended.push(parent);
+            partials.push(extended);
+        }
+    }
+
+    Some(AllShortestPaths {
+        paths,
+        cost: search.cost,
+        expanded: search.expanded,
+    })
+}
+
+/// The bookkeeping left behind by a search, with states referred to by index
+struct Search<S, C> {
+    states: Vec<S>,
+    /// the optimal predecessors of each state. start states have none
+    parents: Vec<Vec<usize>>,
+    goals: Vec<usize>,
+    cost: C,
+    expanded: usize,
+}
+
+impl<S, C> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+{
+    fn run<IS, FN, IN, FG, FH>(
+        starts: IS,
+        mut successors: FN,
+        mut is_goal: FG,
+        mut heuristic: FH,
+        find_all: bool,
+    ) -> Option<Self>
+    where
+        IS: IntoIterator<Item = S>,
+        FN: FnMut(&S) -> IN,
+        IN: IntoIterator<Item = (S, C)>,
+        FG: FnMut(&S) -> bool,
+        FH: FnMut(&S) -> C,
+    {
+        let mut indices: HashMap<S, usize> = HashMap::new();
+        let mut states: Vec<S> = Vec::new();
+        let mut costs: Vec<C> = Vec::new();
+        let mut parents: Vec<Vec<usize>> = Vec::new();
+        let mut closed: Vec<bool> = Vec::new();
+
+        // this may contain duplicate states (with different costs), but that's ok!
+        // since a `pop` will always return the state with the lowest estimate, stale
+        // entries are simply skipped once their state has been closed
+        //
+        // equal estimates are broken by the lower cost, so a state's tied parents (which are
+        // cheaper) are expanded before it is closed
+        let mut frontier = BinaryHeap::new();
+
+        for start in starts {
+            if indices.contains_key(&start) {
+                continue;
+            }
+            let i = states.len();
+            frontier.push(Reverse((heuristic(&start), C::zero(), i)));
+            indices.insert(start.clone(), i);
+            states.push(start);
+            costs.push(C::zero());
+            parents.push(Vec::new());
+            closed.push(false);
+        }
+
+        let mut goals = Vec::new();
+        let mut best_cost: Option<C> = None;
+        let mut expanded = 0;
+
+        while let Some(Reverse((estimate, _, i))) = frontier.pop() {
+            if closed[i] {
+                continue;
+            }
+            // once every remaining estimate is worse than the best goal, no more ties are possible
+            if best_cost.is_some_and(|best| estimate > best) {
+                break;
+            }
+            closed[i] = true;
+
+            let cost = costs[i];
+            if is_goal(&states[i]) {
+                goals.push(i);
+                best_cost = Some(cost);
+                if !find_all {
+                    break;
+                }
+                continue;
+            }
+
+            expanded += 1;
+            for (successor, step_cost) in successors(&states[i]) {
+                let new_cost = cost + step_cost;
+                match indices.get(&successor) {
+                    Some(&j) if closed[j] => {
+                        // an inconsistent heuristic can close a goal before all of its tied
+                        // parents are expanded. nothing is expanded from a goal, so giving it
+                        // another parent can't make a cycle
+                        if find_all
+                            && new_cost == costs[j]
+                            && goals.contains(&j)
+                            && !parents[j].contains(&i)
+                        {
+                            parents[j].push(i);
+                        }
+                    }
+                    Some(&j) => {
+                        if new_cost < costs[j] {
+                            costs[j] = new_cost;
+                            parents[j] = vec![i];
+                            frontier.push(Reverse((new_cost + heuristic(&states[j]), new_cost, j)));
+                        } else if new_cost == costs[j] && !parents[j].contains(&i) {
+                            parents[j].push(i);
+                        }
+                    }
+                    None => {
+                        let j = states.len();
+                        frontier.push(Reverse((new_cost + heuristic(&successor), new_cost, j)));
+                        indices.insert(successor.clone(), j);
+                        states.push(successor);
+                        costs.push(new_cost);
+                        parents.push(vec![i]);
+                        closed.push(false);
+                    }
+                }
+            }
+        }
+
+        Some(Self {
+            states,
+            parents,
+            goals,
+            cost: best_cost?,
+            expanded,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    type Graph = HashMap<char, Vec<(char, u32)>>;
+
+    fn graph(edges: &[(char, char, u32)]) -> Graph {
+        let mut graph = Graph::new();
+        for &(from, to, cost) in edges {
+            graph.entry(from).or_default().push((to, cost));
+        }
+        graph
+    }
+
+    fn successors(graph: &Graph) -> impl FnMut(&char) -> Vec<(char, u32)> + '_ {
+        |state| graph.get(state).cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_multiple_starts() {
+        let graph = graph(&[('a', 'g', 5), ('b', 'g', 2)]);
+
+        let found = shortest_path(['a', 'b'], successors(&graph), |&s| s == 'g', |_| 0).unwrap();
+        assert_eq!(found.path, ['b', 'g']);
+        assert_eq!(found.cost, 2);
+    }
+
+    #[test]
+    fn test_multiple_goals() {
+        let graph = graph(&[('s', 'x', 3), ('s', 'a', 1), ('a', 'y', 1)]);
+
+        let is_goal = |s: &char| *s == 'x' || *s == 'y';
+        let found = shortest_path(['s'], successors(&graph), is_goal, |_| 0).unwrap();
+        assert_eq!(found.path, ['s', 'a', 'y']);
+        assert_eq!(found.cost, 2);
+
+        assert_eq!(
+            shortest_path(['s'], successors(&graph), |&s| s == 'z', |_| 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_expanded() {
+        // the expensive branch is never expanded, since the goal is closed first
+        let graph = graph(&[('s', 'a', 1), ('a', 'b', 1), ('b', 't', 1), ('s', 'x', 10)]);
+
+        let found = shortest_path(['s'], successors(&graph), |&s| s == 't', |_| 0).unwrap();
+        assert_eq!(found.path, ['s', 'a', 'b', 't']);
+        assert_eq!(found.expanded, 3);
+
+        // starting on a goal doesn't expand anything at all
+        let found = shortest_path(['t'], successors(&graph), |&s| s == 't', |_| 0).unwrap();
+        assert_eq!((found.path, found.cost, found.expanded), (vec!['t'], 0, 0));
+    }
+
+    #[test]
+    fn test_all_shortest_paths_diamond() {
+        let graph = graph(&[
+            ('s', 'a', 1),
+            ('s', 'b', 1),
+            ('a', 't', 1),
+            ('b', 't', 1),
+            ('s', 't', 3),
+        ]);
+
+        let found = all_shortest_paths(['s'], successors(&graph), |&s| s == 't', |_| 0).unwrap();
+        let mut paths = found.paths;
+        paths.sort();
+        assert_eq!(paths, [['s', 'a', 't'], ['s', 'b', 't']]);
+        assert_eq!(found.cost, 2);
+    }
+
+    #[test]
+    fn test_all_shortest_paths_with_heuristic() {
+        // both paths cost 2, but a's estimate ties with t's, so t could close before a
+        let edges = graph(&[('s', 't', 2), ('s', 'a', 1), ('a', 't', 1)]);
+        let heuristic = |s: &char| if *s == 'a' { 1 } else { 0 };
+
+        let found =
+            all_shortest_paths(['s'], successors(&edges), |&s| s == 't', heuristic).unwrap();
+        let mut paths = found.paths;
+        paths.sort();
+        assert_eq!(paths, [vec!['s', 'a', 't'], vec!['s', 't']]);
+        assert_eq!(found.cost, 2);
+
+        // a tie in the middle of the path, where x's estimate ties with a's
+        let edges = graph(&[('s', 'x', 2), ('s', 'a', 1), ('a', 'x', 1), ('x', 't', 1)]);
+        let heuristic = |s: &char| match s {
+            'a' => 2,
+            'x' => 1,
+            _ => 0,
+        };
+
+        let found =
+            all_shortest_paths(['s'], successors(&edges), |&s| s == 't', heuristic).unwrap();
+        let mut paths = found.paths;
+        paths.sort();
+        assert_eq!(paths, [vec!['s', 'a', 'x', 't'], vec!['s', 'x', 't']]);
+    }
+
+    #[test]
+    fn test_all_shortest_paths_tied_after_goal_closes() {
+        // a's last step is free, so t and a tie exactly and t is closed before a is expanded
+        let edges = graph(&[('s', 't', 2), ('s', 'a', 2), ('a', 't', 0)]);
+
+        let found = all_shortest_paths(['s'], successors(&edges), |&s| s == 't', |_| 0).unwrap();
+        let mut paths = found.paths;
+        paths.sort();
+        assert_eq!(paths, [vec!['s', 'a', 't'], vec!['s', 't']]);
+    }
+
+    #[test]
+    fn test_all_shortest_paths_to_tied_goals() {
+        let graph = graph(&[('s', 'x', 2), ('s', 'y', 2), ('s', 'z', 3)]);
+
+        let is_goal = |s: &char| s.is_alphabetic() && *s != 's';
+        let found = all_shortest_paths(['s'], successors(&graph), is_goal, |_| 0).unwrap();
+        let mut paths = found.paths;
+        paths.sort();
+        assert_eq!(paths, [['s', 'x'], ['s', 'y']]);
+    }
+
+    #[test]
+    fn test_all_shortest_paths_zero_cost_cycle() {
+        // a and b can be swapped between for free, so the parent links must not loop
+        let graph = graph(&[
+            ('s', 'a', 1),
+            ('s', 'b', 1),
+            ('a', 'b', 0),
+            ('b', 'a', 0),
+            ('a', 't', 1),
+            ('b', 't', 1),
+        ]);
+
+        let found = all_shortest_paths(['s'], successors(&graph), |&s| s == 't', |_| 0).unwrap();
+        assert_eq!(found.cost, 2);
+        assert!(found.paths.len() >= 2);
+        for path in &found.paths {
+            assert_eq!((path.first(), path.last()), (Some(&'s'), Some(&'t')));
+            assert!(path.iter().all_unique());
+        }
+    }
+}
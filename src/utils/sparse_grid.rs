@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use super::{GridBounds, GridEntry, GridLike, GridPoint};
+
+/// A grid which only stores the points that have been set
+///
+/// Useful for mostly-empty or unbounded coordinate spaces, where a dense [`Grid`](super::Grid)
+/// would spend most of its memory (and time spent expanding) on empty cells.
+///
+/// Points that have never been set, but lie within bounds, read as the grid's fill value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    data: HashMap<GridPoint, T>,
+    fill: T,
+    bounds: Option<GridBounds>,
+}
+
+impl<T> SparseGrid<T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    pub fn new() -> Self {
+        Self::with_fill(T::default())
+    }
+
+    pub fn with_fill(fill: T) -> Self {
+        Self {
+            data: HashMap::new(),
+            fill,
+            bounds: None,
+        }
+    }
+
+    /// The number of points which have been set
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn bounds(&self) -> Option<GridBounds> {
+        self.bounds
+    }
+
+    pub fn is_within_bounds(&self, point: &GridPoint) -> bool {
+        self.bounds.is_some_and(|bounds| bounds.contains(point))
+    }
+
+    /// Whether `point` has been explicitly set, as opposed to just reading as the fill value
+    pub fn contains(&self, point: &GridPoint) -> bool {
+        self.data.contains_key(point)
+    }
+
+    pub fn get(&self, point: &GridPoint) -> Option<&T> {
+        match self.data.get(point) {
+            Some(value) => Some(value),
+            None if self.is_within_bounds(point) => Some(&self.fill),
+            None => None,
+        }
+    }
+
+    /// Like [`get`](Self::get), a point within bounds that was never set gives the fill value,
+    /// which is stored at that point first so that changes to it stick
+    pub fn get_mut(&mut self, point: &GridPoint) -> Option<&mut T> {
+        if !self.is_within_bounds(point) {
+            return None;
+        }
+        Some(self.data.entry(*point).or_insert_with(|| self.fill.clone()))
+    }
+
+    /// Set the value at (x, y)
+    ///
+    /// Sparse grids have no edges, so this always succeeds and grows the bounds as needed
+    pub fn set(&mut self, point: &GridPoint, value: T) -> Result<(), String> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.expand_to(point),
+            None => self.bounds = Some(GridBounds::around(*point)),
+        }
+        self.data.insert(*point, value);
+        Ok(())
+    }
+
+    /// Unset the value at (x, y), so that it reads as the fill value again
+    ///
+    /// The bounds are recomputed from the remaining points, which is linear in the number of points
+    pub fn remove(&mut self, point: &GridPoint) -> Option<T> {
+        let removed = self.data.remove(point)?;
+
+        let mut points = self.data.keys();
+        self.bounds = points.next().map(|first| {
+            let mut bounds = GridBounds::around(*first);
+            for point in points {
+                bounds.expand_to(point);
+            }
+            bounds
+        });
+
+        Some(removed)
+    }
+
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<GridEntry<&T>> {
+        self.entries().find(|entry| predicate(entry.value))
+    }
+
+    pub fn num_matching(&self, predicate: impl Fn(&T) -> bool) -> usize {
+        self.entries_matching(predicate).count()
+    }
+
+    pub fn entries_matching(
+        &self,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> impl Iterator<Item = GridEntry<&T>> {
+        self.entries().filter(move |entry| predicate(entry.value))
+    }
+
+    /// All points which have been set, in no particular order
+    pub fn entries(&self) -> impl Iterator<Item = GridEntry<&T>> {
+        self.data.iter().map(|(point, value)| GridEntry {
+            point: *point,
+            value,
+        })
+    }
+}
+
+impl<T> Default for SparseGrid<T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> GridLike<T> for SparseGrid<T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    fn get(&self, point: &GridPoint) -> Option<&T> {
+        SparseGrid::get(self, point)
+    }
+
+    fn set(&mut self, point: &GridPoint, value: T) -> Result<(), String> {
+        SparseGrid::set(self, point, value)
+    }
+
+    fn bounds(&self) -> Option<GridBounds> {
+        SparseGrid::bounds(self)
+    }
+
+    fn entries<'a>(&'a self) -> impl Iterator<Item = GridEntry<&'a T>>
+    where
+        T: 'a,
+    {
+        SparseGrid::entries(self)
+    }
+}
+
+impl<T> FromIterator<(GridPoint, T)> for SparseGrid<T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    fn from_iter<I: IntoIterator<Item = (GridPoint, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (point, value) in iter {
+            grid.set(&point, value)
+                .expect("sparse grids accept any point");
+        }
+        grid
+    }
+}
+
+impl<T> std::fmt::Display for SparseGrid<T>
+where
+    T: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(bounds) = self.bounds else {
+            return Ok(());
+        };
+
+        for y in bounds.top_left.y..=bounds.bottom_right.y {
+            for x in bounds.top_left.x..=bounds.bottom_right.x {
+                let value = self.data.get(&GridPoint { x, y }).unwrap_or(&self.fill);
+                write!(f, "{}", value)?;
+            }
+            if y < bounds.bottom_right.y {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_and_set() {
+        let mut grid = SparseGrid::with_fill('.');
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.get(&(0, 0).into()), None);
+
+        grid.set(&(1, 1).into(), '#').unwrap();
+        grid.set(&(-2, 3).into(), '#').unwrap();
+        assert_eq!(
+            grid.bounds(),
+            Some(GridBounds::new((-2, 1).into(), (1, 3).into()))
+        );
+        assert_eq!(grid.len(), 2);
+
+        // unset points read as the fill inside the bounds, and as nothing outside them
+        assert_eq!(grid.get(&(1, 1).into()), Some(&'#'));
+        assert_eq!(grid.get(&(0, 2).into()), Some(&'.'));
+        assert!(!grid.contains(&(0, 2).into()));
+        assert_eq!(grid.get(&(2, 2).into()), None);
+        assert_eq!(grid.to_string(), "...#\n....\n#...");
+
+        assert_eq!(grid.remove(&(-2, 3).into()), Some('#'));
+        assert_eq!(grid.bounds(), Some(GridBounds::around((1, 1).into())));
+        assert_eq!(grid.get(&(0, 2).into()), None);
+    }
+
+    #[test]
+    fn test_get_mut_matches_get() {
+        let mut grid = SparseGrid::with_fill(0);
+        grid.set(&(0, 0).into(), 1).unwrap();
+        grid.set(&(2, 2).into(), 1).unwrap();
+
+        *grid.get_mut(&(1, 1).into()).unwrap() += 5;
+        assert_eq!(grid.get(&(1, 1).into()), Some(&5));
+        assert_eq!(grid.len(), 3);
+
+        assert_eq!(grid.get_mut(&(3, 3).into()), None);
+        assert_eq!(
+            grid.bounds(),
+            Some(GridBounds::new((0, 0).into(), (2, 2).into()))
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use super::{astar_distance_orthogonal_by, Grid, GridPoint};
+
+/// A view over a [`Grid`] that repeats it infinitely in every direction
+///
+/// Any point, no matter how far outside the underlying grid, maps back onto a cell of the
+/// grid by wrapping its coordinates around. No data is copied, so this is much cheaper
+/// than physically tiling a grid with `append_in_direction`
+#[derive(Debug, Clone, Copy)]
+pub struct TiledGrid<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<'a, T> TiledGrid<'a, T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    pub fn new(grid: &'a Grid<T>) -> Self {
+        Self { grid }
+    }
+
+    pub fn grid(&self) -> &'a Grid<T> {
+        self.grid
+    }
+
+    /// Maps a point anywhere on the infinite plane to the equivalent point within the underlying grid
+    ///
+    /// Returns `None` if the underlying grid is empty, since then there is nothing to wrap onto
+    pub fn wrap(&self, point: &GridPoint) -> Option<GridPoint> {
+        let (width, height) = self.nonzero_dimensions()?;
+        let top_left = self.grid.top_left();
+
+        Some(GridPoint {
+            x: top_left.x + (point.x - top_left.x).rem_euclid(width),
+            y: top_left.y + (point.y - top_left.y).rem_euclid(height),
+        })
+    }
+
+    /// Gets which copy of the underlying grid a point falls into
+    ///
+    /// The original grid is tile `(0, 0)`, the copy directly to its east is `(1, 0)`,
+    /// the copy directly to its north is `(0, -1)`, and so on
+    pub fn tile_of(&self, point: &GridPoint) -> Option<GridPoint> {
+        let (width, height) = self.nonzero_dimensions()?;
+        let top_left = self.grid.top_left();
+
+        Some(GridPoint {
+            x: (point.x - top_left.x).div_euclid(width),
+            y: (point.y - top_left.y).div_euclid(height),
+        })
+    }
+
+    /// Gets the top-left point of a given tile, in the coordinates of the infinite plane
+    pub fn tile_top_left(&self, tile: &GridPoint) -> GridPoint {
+        let top_left = self.grid.top_left();
+        GridPoint {
+            x: top_left.x + tile.x * self.grid.width() as i64,
+            y: top_left.y + tile.y * self.grid.height() as i64,
+        }
+    }
+
+    pub fn get(&self, point: &GridPoint) -> Option<&'a T> {
+        self.wrap(point).and_then(|wrapped| self.grid.get(&wrapped))
+    }
+
+    /// Get the value at (x, y), wrapping it onto the underlying grid
+    ///
+    /// # Panics
+    ///
+    /// Panics if the underlying grid is empty
+    pub fn must_get(&self, point: &GridPoint) -> &'a T {
+        self.get(point)
+            .expect("cannot index into an empty tiled grid")
+    }
+
+    /// Copies a rectangular window of the infinite plane into a regular, finite grid
+    ///
+    /// The returned grid keeps the coordinates of the plane, so its top left is `top_left`
+    pub fn window(
+        &self,
+        top_left: &GridPoint,
+        width: usize,
+        height: usize,
+    ) -> Result<Grid<T>, String> {
+        if self.nonzero_dimensions().is_none() {
+            return Err("Cannot take a window of an empty tiled grid".to_string());
+        }
+
+        let mut window = Grid::new_empty(width, height);
+        window.set_top_left(*top_left);
+        for y in top_left.y..top_left.y + height as i64 {
+            for x in top_left.x..top_left.x + width as i64 {
+                let point = GridPoint { x, y };
+                window.set(&point, self.must_get(&point).clone())?;
+            }
+        }
+        Ok(window)
+    }
+
+    /// Compute the shortest distance between two points on the infinite plane using A*,
+    /// giving up on any path longer than `max_distance`
+    ///
+    /// The plane has no edges, so only points within `max_distance` steps of `start` are
+    /// searched. That keeps the search finite even when `end` is walled off, in which case this
+    /// returns `None`
+    pub fn astar_distance_orthogonal(
+        &self,
+        start: &GridPoint,
+        end: &GridPoint,
+        max_distance: u64,
+        traversable: impl Fn(&T) -> bool,
+    ) -> Option<u64> {
+        astar_distance_orthogonal_by(start, end, |point| {
+            start.manhattan_distance_to(point) <= max_distance
+                && self.get(point).is_some_and(&traversable)
+        })
+        .filter(|&distance| distance <= max_distance)
+    }
+
+    fn nonzero_dimensions(&self) -> Option<(i64, i64)> {
+        match self.grid.dimensions() {
+            (0, _) | (_, 0) => None,
+            (width, height) => Some((width as i64, height as i64)),
+        }
+    }
+}
+
+impl<'a, T> From<&'a Grid<T>> for TiledGrid<'a, T>
+where
+    T: Default + std::fmt::Debug + Clone + PartialEq,
+{
+    fn from(grid: &'a Grid<T>) -> Self {
+        Self::new(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        Grid::from_data(rows.iter().map(|row| row.chars().collect()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_wrap_negative_points() {
+        let grid = grid(&["abc", "def"]);
+        let tiled = TiledGrid::new(&grid);
+
+        assert_eq!(tiled.wrap(&(-1, -1).into()), Some((2, 1).into()));
+        assert_eq!(tiled.wrap(&(-3, -2).into()), Some((0, 0).into()));
+        assert_eq!(tiled.wrap(&(-4, 5).into()), Some((2, 1).into()));
+        assert_eq!(tiled.get(&(-1, 0).into()), Some(&'c'));
+        assert_eq!(tiled.get(&(7, -3).into()), Some(&'e'));
+
+        assert_eq!(tiled.tile_of(&(0, 0).into()), Some((0, 0).into()));
+        assert_eq!(tiled.tile_of(&(-1, -1).into()), Some((-1, -1).into()));
+        assert_eq!(tiled.tile_of(&(-3, -2).into()), Some((-1, -1).into()));
+        assert_eq!(tiled.tile_of(&(-4, 2).into()), Some((-2, 1).into()));
+        assert_eq!(tiled.tile_top_left(&(-2, 1).into()), (-6, 2).into());
+    }
+
+    #[test]
+    fn test_wrap_with_offset_top_left() {
+        let mut grid = grid(&["ab", "cd"]);
+        grid.set_top_left((10, -5).into());
+        let tiled = TiledGrid::new(&grid);
+
+        assert_eq!(tiled.wrap(&(9, -6).into()), Some((11, -4).into()));
+        assert_eq!(tiled.tile_of(&(9, -6).into()), Some((-1, -1).into()));
+        assert_eq!(tiled.tile_top_left(&(-1, -1).into()), (8, -7).into());
+        assert_eq!(tiled.get(&(8, -7).into()), Some(&'a'));
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        let grid: Grid<char> = Grid::new_empty(0, 0);
+        let tiled = TiledGrid::new(&grid);
+
+        assert_eq!(tiled.wrap(&(1, 1).into()), None);
+        assert_eq!(tiled.tile_of(&(1, 1).into()), None);
+        assert!(tiled.window(&(0, 0).into(), 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_window() {
+        let grid = grid(&["ab", "cd"]);
+        let tiled = TiledGrid::new(&grid);
+
+        let window = tiled.window(&(-1, -1).into(), 3, 3).unwrap();
+        assert_eq!(*window.top_left(), GridPoint::from((-1, -1)));
+        let rows: Vec<String> = window.rows_iter().map(|row| row.collect()).collect();
+        assert_eq!(rows, ["dcd", "bab", "dcd"]);
+    }
+
+    #[test]
+    fn test_astar_across_seams() {
+        // the wall in the middle column repeats in every tile, with a gap along the bottom row
+        let grid = grid(&[".#.", ".#.", "..."]);
+        let tiled = TiledGrid::new(&grid);
+        let is_open = |c: &char| *c == '.';
+        let distance = |start: (i64, i64), end: (i64, i64)| {
+            tiled.astar_distance_orthogonal(&start.into(), &end.into(), 20, is_open)
+        };
+
+        // the quickest way around the wall is up over the seam, through the gap in the bottom
+        // row of the tile above, which is shorter than going around inside this tile
+        assert_eq!(distance((0, 0), (2, 0)), Some(4));
+        assert_eq!(distance((0, 0), (-1, 0)), Some(1));
+
+        // the same trips around the wall, in tiles with negative coordinates
+        assert_eq!(distance((-1, 0), (-3, 0)), Some(4));
+        assert_eq!(distance((-4, -4), (-6, -4)), Some(2));
+
+        // straight up through several tiles
+        assert_eq!(distance((0, -1), (0, -8)), Some(7));
+    }
+
+    #[test]
+    fn test_astar_bounded() {
+        // every tile's middle point is boxed in, so it can never be reached
+        let pillars = grid(&["...", ".#.", "..."]);
+        let boxed = grid(&["###", "#.#", "###"]);
+        let is_open = |c: &char| *c == '.';
+
+        let tiled = TiledGrid::new(&boxed);
+        assert_eq!(
+            tiled.astar_distance_orthogonal(&(1, 1).into(), &(4, 1).into(), 100, is_open),
+            None
+        );
+
+        // the goal is 3 steps away in a straight line, but going around the pillar takes 5
+        let tiled = TiledGrid::new(&pillars);
+        let (start, end) = ((0, 1).into(), (3, 1).into());
+        assert_eq!(
+            tiled.astar_distance_orthogonal(&start, &end, 5, is_open),
+            Some(5)
+        );
+        assert_eq!(
+            tiled.astar_distance_orthogonal(&start, &end, 4, is_open),
+            None
+        );
+    }
+}
@@ -35,3 +35,215 @@ pub fn format_duration(d: Duration) -> String {
 
     s
 }
+
+/// Time everything from here to the end of the enclosing scope, nested under whichever span is
+/// already open on this thread
+///
+/// Bind the guard to a name, or it's dropped (and the span closed) straight away:
+/// `let _span = span!("settle");`. Without the `profile` feature this compiles to nothing.
+#[cfg(feature = "profile")]
+macro_rules! span {
+    ($name:expr) => {
+        $crate::utils::SpanGuard::enter($name)
+    };
+}
+
+#[cfg(not(feature = "profile"))]
+macro_rules! span {
+    ($name:expr) => {
+        ()
+    };
+}
+
+pub(crate) use span;
+
+#[cfg(feature = "profile")]
+pub use profile::*;
+
+#[cfg(feature = "profile")]
+mod profile {
+    use std::{
+        cell::RefCell,
+        time::{Duration, Instant},
+    };
+
+    use super::format_duration;
+
+    thread_local! {
+        static SPANS: RefCell<SpanTree> = RefCell::new(SpanTree::default());
+    }
+
+    #[derive(Debug, Default)]
+    struct SpanTree {
+        nodes: Vec<SpanNode>,
+        /// The spans at the top level, in the order they were first opened
+        roots: Vec<usize>,
+        /// The innermost span that's currently open
+        current: Option<usize>,
+    }
+
+    #[derive(Debug)]
+    struct SpanNode {
+        name: &'static str,
+        parent: Option<usize>,
+        children: Vec<usize>,
+        calls: u32,
+        total: Duration,
+    }
+
+    impl SpanTree {
+        /// Open the span called `name` under the current one, reusing it if it's been opened
+        /// there before so repeated calls add up
+        fn enter(&mut self, name: &'static str) -> usize {
+            let siblings = match self.current {
+                Some(parent) => &self.nodes[parent].children,
+                None => &self.roots,
+            };
+            let index = match siblings.iter().find(|&&i| self.nodes[i].name == name) {
+                Some(&index) => index,
+                None => {
+                    let index = self.nodes.len();
+                    self.nodes.push(SpanNode {
+                        name,
+                        parent: self.current,
+                        children: Vec::new(),
+                        calls: 0,
+                        total: Duration::ZERO,
+                    });
+                    match self.current {
+                        Some(parent) => self.nodes[parent].children.push(index),
+                        None => self.roots.push(index),
+                    }
+                    index
+                }
+            };
+            self.current = Some(index);
+            index
+        }
+
+        fn exit(&mut self, index: usize, elapsed: Duration) {
+            let node = &mut self.nodes[index];
+            node.calls += 1;
+            node.total += elapsed;
+            self.current = node.parent;
+        }
+
+        fn report(&self) -> String {
+            if self.roots.is_empty() {
+                return String::new();
+            }
+
+            let mut rows = Vec::new();
+            let mut stack = self.roots.iter().rev().map(|&i| (i, 0)).collect::<Vec<_>>();
+            while let Some((index, depth)) = stack.pop() {
+                let node = &self.nodes[index];
+                let children_total = node.children.iter().map(|&i| self.nodes[i].total).sum();
+                rows.push((
+                    format!("{}{}", "  ".repeat(depth), node.name),
+                    node.total,
+                    node.total.saturating_sub(children_total),
+                    node.calls,
+                ));
+                stack.extend(node.children.iter().rev().map(|&i| (i, depth + 1)));
+            }
+
+            let width = rows.iter().map(|(name, ..)| name.len()).max().unwrap_or(0);
+            let mut report = format!(
+                "{:<width$}  {:>12}  {:>12}  {:>8}\n",
+                "span", "total", "self", "calls"
+            );
+            for (name, total, self_time, calls) in rows {
+                report.push_str(&format!(
+                    "{:<width$}  {:>12}  {:>12}  {:>8}\n",
+                    name,
+                    format_duration(total),
+                    format_duration(self_time),
+                    calls
+                ));
+            }
+            report
+        }
+    }
+
+    /// Closes its span when dropped, made by `span!`
+    #[must_use = "the span closes as soon as the guard is dropped"]
+    pub struct SpanGuard {
+        index: usize,
+        start: Instant,
+    }
+
+    impl SpanGuard {
+        pub fn enter(name: &'static str) -> Self {
+            let index = SPANS.with(|spans| spans.borrow_mut().enter(name));
+            Self {
+                index,
+                start: Instant::now(),
+            }
+        }
+    }
+
+    impl Drop for SpanGuard {
+        fn drop(&mut self) {
+            let elapsed = self.start.elapsed();
+            SPANS.with(|spans| spans.borrow_mut().exit(self.index, elapsed));
+        }
+    }
+
+    /// An indented tree of every span recorded on this thread so far, with the total time spent
+    /// in each, the time spent outside its child spans, and how many times it was entered
+    ///
+    /// This is empty if no spans have been recorded
+    pub fn profile_report() -> String {
+        SPANS.with(|spans| spans.borrow().report())
+    }
+
+    /// Forget every span recorded on this thread, so the next report starts from scratch
+    pub fn reset_profile() {
+        SPANS.with(|spans| *spans.borrow_mut() = SpanTree::default());
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_span_report() {
+            let ms = Duration::from_millis;
+            let mut spans = SpanTree::default();
+            let outer = spans.enter("outer");
+            for _ in 0..3 {
+                let inner = spans.enter("inner");
+                spans.exit(inner, ms(2));
+            }
+            let other = spans.enter("other");
+            spans.exit(other, ms(1));
+            spans.exit(outer, ms(10));
+            let outer = spans.enter("outer");
+            spans.exit(outer, ms(5));
+
+            assert_eq!(
+                spans.report(),
+                "\
+span            total          self     calls
+outer         15.00ms        8.00ms         2
+  inner        6.00ms        6.00ms         3
+  other        1.00ms        1.00ms         1
+"
+            );
+        }
+
+        #[test]
+        fn test_span_guards_nest() {
+            reset_profile();
+            {
+                let _outer = span!("outer");
+                let _inner = span!("inner");
+            }
+            let report = profile_report();
+            let mut rows = report.lines().skip(1);
+            assert!(rows.next().is_some_and(|row| row.starts_with("outer ")));
+            assert!(rows.next().is_some_and(|row| row.starts_with("  inner ")));
+            assert_eq!(rows.next(), None);
+        }
+    }
+}